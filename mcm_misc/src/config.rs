@@ -0,0 +1,392 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+
+use crate::error::MCManageError;
+
+/// Configuration shared by the applications in the MCManage Network.
+///
+/// Derives [`Serialize`]/[`Deserialize`] so the whole config (not just the
+/// hot-reloadable subset in [`ConfigFile`]) can round-trip through a
+/// settings UI or a config file read with e.g. the `toml` crate;
+/// [`Self::bind_retry_delay`] is written out as a human-readable duration
+/// string (`"3s"`) rather than a raw number via `humantime_serde`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// The directory under which every managed Minecraft server's files live.
+    ///
+    /// Not hot-reloadable: every managed server's on-disk path is derived
+    /// from this once at startup, so changing it live would silently
+    /// orphan already-running servers. See [`ConfigFile`].
+    pub server_path: PathBuf,
+    /// Whether the EULA should be accepted automatically on behalf of the operator.
+    pub agree_to_eula: bool,
+    /// How many additional attempts the Communicator makes to bind its
+    /// listening socket before giving up; see [`Self::with_bind_retries`].
+    pub bind_retries: u32,
+    /// How long the Communicator waits between bind attempts; see
+    /// [`Self::with_bind_retry_delay`].
+    #[serde(with = "humantime_serde")]
+    pub bind_retry_delay: Duration,
+    /// How many messages read off handler connections may queue up waiting
+    /// to be processed before a handler pauses reading further messages;
+    /// see [`Self::with_inbound_queue_capacity`].
+    pub inbound_queue_capacity: usize,
+    /// The address the Communicator listens on by default; see
+    /// [`Self::with_bind_address`].
+    pub bind_address: SocketAddr,
+    /// How long an RCON command waits for a reply before giving up; see
+    /// [`Self::with_rcon_command_timeout`].
+    #[serde(with = "humantime_serde")]
+    pub rcon_command_timeout: Duration,
+}
+
+impl Config {
+    pub fn new(server_path: PathBuf, agree_to_eula: bool) -> Self {
+        Self {
+            server_path,
+            agree_to_eula,
+            bind_retries: 3,
+            bind_retry_delay: Duration::from_secs(3),
+            inbound_queue_capacity: 128,
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 7863)),
+            rcon_command_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Overrides how many additional attempts the Communicator makes to
+    /// bind its listening socket before giving up (default 3).
+    pub fn with_bind_retries(mut self, bind_retries: u32) -> Self {
+        self.bind_retries = bind_retries;
+        self
+    }
+
+    /// Overrides how long the Communicator waits between bind attempts
+    /// (default 3 seconds).
+    pub fn with_bind_retry_delay(mut self, bind_retry_delay: Duration) -> Self {
+        self.bind_retry_delay = bind_retry_delay;
+        self
+    }
+
+    /// Overrides how many messages may queue up waiting to be processed
+    /// before a handler pauses reading further messages off its connection
+    /// (default 128), so a slow consumer applies backpressure instead of
+    /// letting messages buffer without limit.
+    pub fn with_inbound_queue_capacity(mut self, inbound_queue_capacity: usize) -> Self {
+        self.inbound_queue_capacity = inbound_queue_capacity;
+        self
+    }
+
+    /// Overrides the address the Communicator listens on by default
+    /// (default `127.0.0.1:7863`).
+    pub fn with_bind_address(mut self, bind_address: SocketAddr) -> Self {
+        self.bind_address = bind_address;
+        self
+    }
+
+    /// Overrides how long an RCON command waits for a reply before giving
+    /// up (default 5 seconds).
+    pub fn with_rcon_command_timeout(mut self, rcon_command_timeout: Duration) -> Self {
+        self.rcon_command_timeout = rcon_command_timeout;
+        self
+    }
+
+    /// Applies `file`'s hot-reloadable overrides on top of this config.
+    /// Any field `file` doesn't set (and [`Self::server_path`], which
+    /// [`ConfigFile`] never sets) is left unchanged.
+    ///
+    /// Errors if `file.bind_address` is set but fails to parse; see
+    /// [`parse_socket_addr`].
+    pub fn apply(&self, file: &ConfigFile) -> Result<Config, MCManageError> {
+        let mut updated = self.clone();
+        if let Some(agree_to_eula) = file.agree_to_eula {
+            updated.agree_to_eula = agree_to_eula;
+        }
+        if let Some(bind_retries) = file.bind_retries {
+            updated.bind_retries = bind_retries;
+        }
+        if let Some(bind_retry_delay_secs) = file.bind_retry_delay_secs {
+            updated.bind_retry_delay = Duration::from_secs(bind_retry_delay_secs);
+        }
+        if let Some(inbound_queue_capacity) = file.inbound_queue_capacity {
+            updated.inbound_queue_capacity = inbound_queue_capacity;
+        }
+        if let Some(bind_address) = &file.bind_address {
+            updated.bind_address = parse_socket_addr("bind_address", bind_address)?;
+        }
+        if let Some(rcon_command_timeout_secs) = file.rcon_command_timeout_secs {
+            updated.rcon_command_timeout = Duration::from_secs(rcon_command_timeout_secs);
+        }
+        Ok(updated)
+    }
+
+    /// Watches `path` for changes (e.g. an operator hand-editing it) and
+    /// calls `callback` with a freshly [`Self::apply`]-ed [`Config`] every
+    /// time it changes, so already-running components can pick up
+    /// safe-to-change fields like [`Self::bind_retries`] or
+    /// [`Self::inbound_queue_capacity`] without a restart.
+    ///
+    /// `path` is read as JSON shaped like [`ConfigFile`]. A write that
+    /// fails to parse is logged and skipped rather than propagated, since
+    /// one bad edit shouldn't take down the watcher. The returned
+    /// [`ConfigWatch`] must be kept alive for as long as watching should
+    /// continue; dropping it stops the background watcher.
+    pub fn watch(
+        &self,
+        path: impl Into<PathBuf>,
+        callback: impl Fn(Config) + Send + 'static,
+    ) -> Result<ConfigWatch, MCManageError> {
+        let path = path.into();
+        let base = self.clone();
+        let (tx, rx) = channel::<notify::Result<Event>>();
+
+        let mut watcher =
+            notify::recommended_watcher(tx).map_err(|err| MCManageError::InvalidFile(path.clone(), err.to_string()))?;
+        watcher
+            .watch(&path, RecursiveMode::NonRecursive)
+            .map_err(|err| MCManageError::InvalidFile(path.clone(), err.to_string()))?;
+
+        let watched_path = path.clone();
+        let handle = thread::spawn(move || {
+            for event in rx {
+                let Ok(event) = event else { continue };
+                if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                    continue;
+                }
+                match ConfigFile::load(&watched_path).and_then(|file| base.apply(&file)) {
+                    Ok(config) => callback(config),
+                    Err(err) => eprintln!("config watch: could not reload '{}': {err}", watched_path.display()),
+                }
+            }
+        });
+
+        Ok(ConfigWatch {
+            watcher: Some(watcher),
+            handle: Some(handle),
+        })
+    }
+}
+
+/// Parses a `host:port` address read from a config value (e.g. a bind
+/// address), naming `field` and the offending value in the error instead of
+/// propagating std's cryptic [`std::net::AddrParseError`]/lookup failure
+/// verbatim. Validates that the port fits in a `u16` and that the host
+/// resolves to at least one address.
+pub fn parse_socket_addr(field: &str, value: &str) -> Result<SocketAddr, MCManageError> {
+    let invalid = |reason: String| MCManageError::InvalidValue(field.to_string(), format!("'{value}': {reason}"));
+
+    let (host, port) = value.rsplit_once(':').ok_or_else(|| invalid("expected a 'host:port' address".to_string()))?;
+    if host.is_empty() {
+        return Err(invalid("missing host".to_string()));
+    }
+    let port: u16 = port
+        .parse()
+        .map_err(|_| invalid(format!("'{port}' is not a valid port (must be 0-65535)")))?;
+
+    (host, port)
+        .to_socket_addrs()
+        .map_err(|err| invalid(format!("host '{host}' could not be resolved: {err}")))?
+        .next()
+        .ok_or_else(|| invalid(format!("host '{host}' did not resolve to any address")))
+}
+
+/// The hot-reloadable subset of [`Config`], matched 1:1 against the fields
+/// that are safe to change while the process is running; see
+/// [`Config::watch`] and [`Config::apply`]. [`Config::server_path`] is
+/// deliberately absent — see its doc comment for why.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub agree_to_eula: Option<bool>,
+    #[serde(default)]
+    pub bind_retries: Option<u32>,
+    #[serde(default)]
+    pub bind_retry_delay_secs: Option<u64>,
+    #[serde(default)]
+    pub inbound_queue_capacity: Option<usize>,
+    /// A `host:port` address, parsed via [`parse_socket_addr`]; see
+    /// [`Config::bind_address`].
+    #[serde(default)]
+    pub bind_address: Option<String>,
+    #[serde(default)]
+    pub rcon_command_timeout_secs: Option<u64>,
+}
+
+impl ConfigFile {
+    fn load(path: &Path) -> Result<ConfigFile, MCManageError> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|err| MCManageError::InvalidFile(path.to_path_buf(), err.to_string()))
+    }
+}
+
+/// Keeps a [`Config::watch`] background watcher alive. Dropping it stops
+/// watching and joins the background thread.
+pub struct ConfigWatch {
+    watcher: Option<RecommendedWatcher>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for ConfigWatch {
+    fn drop(&mut self) {
+        // Drop the watcher first so its channel closes and the background
+        // thread's `for event in rx` loop ends, instead of joining forever.
+        self.watcher.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel as test_channel;
+    use tempfile::tempdir;
+
+    #[test]
+    fn config_round_trips_through_toml() {
+        let config = Config::new(PathBuf::from("/servers"), true)
+            .with_bind_retries(5)
+            .with_bind_retry_delay(Duration::from_secs(7))
+            .with_inbound_queue_capacity(256);
+
+        let serialized = toml::to_string(&config).unwrap();
+        assert!(
+            serialized.contains("bind_retry_delay = \"7s\""),
+            "expected a human-readable duration, got:\n{serialized}"
+        );
+
+        let deserialized: Config = toml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, config);
+    }
+
+    #[test]
+    fn parse_socket_addr_accepts_a_valid_address() {
+        let addr = parse_socket_addr("bind_address", "127.0.0.1:25565").unwrap();
+        assert_eq!(addr, "127.0.0.1:25565".parse().unwrap());
+    }
+
+    #[test]
+    fn parse_socket_addr_rejects_an_out_of_range_port() {
+        let err = parse_socket_addr("bind_address", "localhost:99999").unwrap_err();
+        match err {
+            MCManageError::InvalidValue(field, reason) => {
+                assert_eq!(field, "bind_address");
+                assert!(reason.contains("not a valid port"), "got: {reason}");
+            }
+            other => panic!("expected InvalidValue, got: {other}"),
+        }
+    }
+
+    #[test]
+    fn parse_socket_addr_rejects_a_missing_host() {
+        let err = parse_socket_addr("bind_address", ":25565").unwrap_err();
+        match err {
+            MCManageError::InvalidValue(field, reason) => {
+                assert_eq!(field, "bind_address");
+                assert!(reason.contains("missing host"), "got: {reason}");
+            }
+            other => panic!("expected InvalidValue, got: {other}"),
+        }
+    }
+
+    #[test]
+    fn apply_overrides_only_the_fields_the_file_sets() {
+        let base = Config::new(PathBuf::from("/servers"), false).with_bind_retries(3);
+
+        let file = ConfigFile {
+            agree_to_eula: Some(true),
+            bind_retries: None,
+            bind_retry_delay_secs: Some(10),
+            inbound_queue_capacity: None,
+            bind_address: None,
+            rcon_command_timeout_secs: None,
+        };
+
+        let updated = base.apply(&file).unwrap();
+        assert!(updated.agree_to_eula);
+        assert_eq!(updated.bind_retries, 3);
+        assert_eq!(updated.bind_retry_delay, Duration::from_secs(10));
+        assert_eq!(updated.inbound_queue_capacity, base.inbound_queue_capacity);
+        assert_eq!(updated.server_path, base.server_path);
+        assert_eq!(updated.bind_address, base.bind_address);
+    }
+
+    #[test]
+    fn apply_parses_and_overrides_the_bind_address() {
+        let base = Config::new(PathBuf::from("/servers"), false);
+
+        let file = ConfigFile {
+            agree_to_eula: None,
+            bind_retries: None,
+            bind_retry_delay_secs: None,
+            inbound_queue_capacity: None,
+            bind_address: Some("127.0.0.1:25565".to_string()),
+            rcon_command_timeout_secs: None,
+        };
+
+        let updated = base.apply(&file).unwrap();
+        assert_eq!(updated.bind_address, "127.0.0.1:25565".parse().unwrap());
+    }
+
+    #[test]
+    fn apply_rejects_an_invalid_bind_address() {
+        let base = Config::new(PathBuf::from("/servers"), false);
+
+        let file = ConfigFile {
+            agree_to_eula: None,
+            bind_retries: None,
+            bind_retry_delay_secs: None,
+            inbound_queue_capacity: None,
+            bind_address: Some("not-an-address".to_string()),
+            rcon_command_timeout_secs: None,
+        };
+
+        assert!(matches!(base.apply(&file), Err(MCManageError::InvalidValue(_, _))));
+    }
+
+    #[test]
+    fn watch_invokes_the_callback_with_the_reloaded_values() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"bind_retries": 3}"#).unwrap();
+
+        let base = Config::new(dir.path().to_path_buf(), false);
+        let (tx, rx) = test_channel::<Config>();
+        let _watch = base
+            .watch(&path, move |config| {
+                let _ = tx.send(config);
+            })
+            .unwrap();
+
+        std::fs::write(&path, r#"{"bind_retries": 9, "inbound_queue_capacity": 64}"#).unwrap();
+
+        let reloaded = rx.recv_timeout(Duration::from_secs(5)).expect("expected the callback to fire");
+        assert_eq!(reloaded.bind_retries, 9);
+        assert_eq!(reloaded.inbound_queue_capacity, 64);
+        // Untouched by the file, carried over from the base config.
+        assert_eq!(reloaded.server_path, dir.path());
+    }
+
+    #[test]
+    fn apply_overrides_the_rcon_command_timeout() {
+        let base = Config::new(PathBuf::from("/servers"), false);
+
+        let file = ConfigFile {
+            agree_to_eula: None,
+            bind_retries: None,
+            bind_retry_delay_secs: None,
+            inbound_queue_capacity: None,
+            bind_address: None,
+            rcon_command_timeout_secs: Some(30),
+        };
+
+        let updated = base.apply(&file).unwrap();
+        assert_eq!(updated.rcon_command_timeout, Duration::from_secs(30));
+    }
+}
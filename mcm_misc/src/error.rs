@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+/// The error type shared by every application in the MCManage Network.
+#[derive(Debug, thiserror::Error)]
+pub enum MCManageError {
+    #[error("the requested item could not be found")]
+    NotFound,
+    #[error("the file at '{0}' is invalid: {1}")]
+    InvalidFile(PathBuf, String),
+    #[error("an IO error occurred: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("the operation could not complete in time: {0}")]
+    NotReady(String),
+    #[error("no server type named '{0}' is configured")]
+    UnknownServerType(String),
+    #[error("'{0}' is not a valid handler id")]
+    InvalidHandlerId(String),
+    #[error("the message could not be parsed: {0}")]
+    InvalidMessage(String),
+    #[error("the value for '{0}' is invalid: {1}")]
+    InvalidValue(String, String),
+}
@@ -0,0 +1,7 @@
+pub mod config;
+pub mod error;
+pub mod message;
+
+pub use config::{parse_socket_addr, Config, ConfigFile, ConfigWatch};
+pub use error::MCManageError;
+pub use message::{set_debug_parse_failures, Message, MessageType};
@@ -0,0 +1,550 @@
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::MCManageError;
+
+/// The role a [`Message`] plays in the request/response/event protocol
+/// spoken between the Communicator and its handlers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageType {
+    Request,
+    Response,
+    Event,
+    Error,
+}
+
+/// A single message exchanged between the Communicator and a connected
+/// runner/client over the MCManage Network protocol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    id: u64,
+    message_type: MessageType,
+    command: String,
+    sender: String,
+    receiver: String,
+    args: Vec<String>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Governs whether [`Message::read_async`] logs a diagnostic for a payload
+/// it fails to parse; see [`set_debug_parse_failures`]. Off by default so a
+/// malformed client's raw bytes never leak into logs unless an operator
+/// opts in while actively debugging.
+static DEBUG_PARSE_FAILURES: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables the diagnostic [`Message::read_async`] logs when a
+/// payload fails to parse: the truncated payload in hex and the `serde`
+/// error, to stderr. Off by default; an operator debugging a malformed
+/// client turns it on, diagnoses, then turns it back off.
+pub fn set_debug_parse_failures(enabled: bool) {
+    DEBUG_PARSE_FAILURES.store(enabled, Ordering::Relaxed);
+}
+
+impl Message {
+    pub fn new(
+        command: impl Into<String>,
+        message_type: MessageType,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        args: Vec<String>,
+    ) -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            message_type,
+            command: command.into(),
+            sender: sender.into(),
+            receiver: receiver.into(),
+            args,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn message_type(&self) -> MessageType {
+        self.message_type
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    pub fn receiver(&self) -> &str {
+        &self.receiver
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn args_len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn has_args(&self) -> bool {
+        !self.args.is_empty()
+    }
+
+    /// Builds a `MessageType::Request` message, so call sites can't
+    /// mismatch the message type.
+    pub fn request(
+        command: impl Into<String>,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        args: Vec<String>,
+    ) -> Self {
+        Self::new(command, MessageType::Request, sender, receiver, args)
+    }
+
+    /// Builds a `MessageType::Response` message.
+    pub fn response(
+        command: impl Into<String>,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        args: Vec<String>,
+    ) -> Self {
+        Self::new(command, MessageType::Response, sender, receiver, args)
+    }
+
+    /// Builds a `MessageType::Event` message.
+    pub fn event(
+        command: impl Into<String>,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        args: Vec<String>,
+    ) -> Self {
+        Self::new(command, MessageType::Event, sender, receiver, args)
+    }
+
+    /// Builds a `MessageType::Error` message.
+    pub fn error(
+        command: impl Into<String>,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        args: Vec<String>,
+    ) -> Self {
+        Self::new(command, MessageType::Error, sender, receiver, args)
+    }
+
+    /// Overrides the sender, e.g. when a handler relays a message on
+    /// another client's behalf.
+    pub fn set_sender(&mut self, sender: impl Into<String>) {
+        self.sender = sender.into();
+    }
+
+    /// Overrides the receiver, e.g. when rewriting a message's addressing
+    /// before forwarding it.
+    pub fn set_receiver(&mut self, receiver: impl Into<String>) {
+        self.receiver = receiver.into();
+    }
+
+    /// Overrides the id, e.g. when a relay needs to preserve the
+    /// originating message's id across a rewrite.
+    pub fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    /// Returns `self` with the sender overridden, for fluent rewriting.
+    pub fn with_sender(mut self, sender: impl Into<String>) -> Self {
+        self.set_sender(sender);
+        self
+    }
+
+    /// Returns `self` with the receiver overridden, for fluent rewriting.
+    pub fn with_receiver(mut self, receiver: impl Into<String>) -> Self {
+        self.set_receiver(receiver);
+        self
+    }
+
+    /// Returns `self` with the id overridden, for fluent rewriting.
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.set_id(id);
+        self
+    }
+
+    /// Renders this message as JSON. Named `to_json_string` rather than
+    /// `to_string` so it doesn't shadow [`ToString`]; use [`Display`] (or
+    /// `ToString::to_string`) where a string is actually needed, since both
+    /// produce the same JSON.
+    pub fn to_json_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders this message as a JSON-RPC 2.0-shaped envelope, for
+    /// interoperating with external tools that speak JSON-RPC — not a
+    /// replacement for the native format ([`Self::to_json_string`]), which
+    /// every internal sender/receiver keeps using. `command`/`args` map to
+    /// `method`/`params` (or, for a response/error, `result`/`error.data`);
+    /// `sender`, `receiver` and an explicit `message_type` are carried as
+    /// extension fields so [`Self::from_jsonrpc`] can reconstruct an
+    /// equivalent [`Message`], which a spec-compliant tool is still free to
+    /// ignore.
+    pub fn to_jsonrpc(&self) -> serde_json::Value {
+        let args: Vec<serde_json::Value> = self.args.iter().cloned().map(serde_json::Value::String).collect();
+        let message_type = match self.message_type {
+            MessageType::Request => "request",
+            MessageType::Response => "response",
+            MessageType::Event => "event",
+            MessageType::Error => "error",
+        };
+
+        let mut envelope = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.id,
+            "message_type": message_type,
+            "sender": self.sender,
+            "receiver": self.receiver,
+        });
+
+        match self.message_type {
+            MessageType::Request => {
+                envelope["method"] = serde_json::Value::String(self.command.clone());
+                envelope["params"] = serde_json::Value::Array(args);
+            }
+            MessageType::Response | MessageType::Event => {
+                envelope["method"] = serde_json::Value::String(self.command.clone());
+                envelope["result"] = serde_json::Value::Array(args);
+            }
+            MessageType::Error => {
+                envelope["error"] = serde_json::json!({
+                    "code": -32000,
+                    "message": self.command,
+                    "data": args,
+                });
+            }
+        }
+        envelope
+    }
+
+    /// Parses a JSON-RPC 2.0-shaped envelope produced by [`Self::to_jsonrpc`]
+    /// back into a [`Message`]. Recognizes a request (`method` + `params`),
+    /// a response/event (`method` + `result`, disambiguated by the
+    /// `message_type` extension field), or an error (`error.message` +
+    /// `error.data`).
+    pub fn from_jsonrpc(value: &serde_json::Value) -> Result<Self, MCManageError> {
+        let id = value
+            .get("id")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| MCManageError::InvalidMessage("missing or invalid 'id'".to_string()))?;
+        let sender = value.get("sender").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let receiver = value.get("receiver").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let is_event = value.get("message_type").and_then(|v| v.as_str()) == Some("event");
+
+        let (message_type, command, args) = if let Some(error) = value.get("error") {
+            let command = error.get("message").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let args = Self::string_array(error.get("data"));
+            (MessageType::Error, command, args)
+        } else if let Some(result) = value.get("result") {
+            let command = value.get("method").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let message_type = if is_event { MessageType::Event } else { MessageType::Response };
+            (message_type, command, Self::string_array(Some(result)))
+        } else if let Some(method) = value.get("method").and_then(|v| v.as_str()) {
+            (MessageType::Request, method.to_string(), Self::string_array(value.get("params")))
+        } else {
+            return Err(MCManageError::InvalidMessage(
+                "envelope has neither 'method', 'result', nor 'error'".to_string(),
+            ));
+        };
+
+        Ok(Self { id, message_type, command, sender, receiver, args })
+    }
+
+    /// Reads a single [`Message`] off `reader`, framed as a 4-byte
+    /// big-endian length prefix followed by its JSON body — the framing
+    /// the (eventual) async Communicator speaks over `tokio::net::TcpStream`.
+    /// Returns `Ok(None)` if `reader` is closed before any bytes of a new
+    /// message arrive; a close in the middle of a message is an error
+    /// rather than a silently truncated read.
+    pub async fn read_async(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<Option<Message>> {
+        let mut len_buf = [0u8; 4];
+        let mut read = 0;
+        while read < len_buf.len() {
+            let n = reader.read(&mut len_buf[read..]).await?;
+            if n == 0 {
+                if read == 0 {
+                    return Ok(None);
+                }
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+            read += n;
+        }
+
+        let len = u32::from_be_bytes(len_buf) as usize;
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload).await?;
+        match serde_json::from_slice(&payload) {
+            Ok(message) => Ok(Some(message)),
+            Err(err) => {
+                if let Some(diagnostic) = Self::parse_failure_diagnostic(&payload, &err) {
+                    eprintln!("{diagnostic}");
+                }
+                Err(io::Error::other(err))
+            }
+        }
+    }
+
+    /// Builds the diagnostic [`Self::read_async`] logs when `payload` fails
+    /// to parse with `err` — the truncated payload in hex plus the `serde`
+    /// error — if [`set_debug_parse_failures`] is enabled. Returns `None`
+    /// when disabled (the default), so callers never pay for or leak a hex
+    /// dump of the payload; split out from [`Self::read_async`] so it can be
+    /// asserted on directly instead of having to capture stderr.
+    fn parse_failure_diagnostic(payload: &[u8], err: &serde_json::Error) -> Option<String> {
+        if !DEBUG_PARSE_FAILURES.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(format!(
+            "Message::read_async: failed to parse a {}-byte payload: {err} (bytes: {})",
+            payload.len(),
+            Self::hex_preview(payload)
+        ))
+    }
+
+    /// Renders up to the first `MAX_PREVIEW_BYTES` of `bytes` as hex, for
+    /// [`Self::parse_failure_diagnostic`], so a multi-megabyte malformed
+    /// payload doesn't flood the log.
+    fn hex_preview(bytes: &[u8]) -> String {
+        const MAX_PREVIEW_BYTES: usize = 64;
+        let preview: String = bytes.iter().take(MAX_PREVIEW_BYTES).map(|byte| format!("{byte:02x}")).collect();
+        if bytes.len() > MAX_PREVIEW_BYTES {
+            format!("{preview}... ({} bytes total)", bytes.len())
+        } else {
+            preview
+        }
+    }
+
+    /// Writes this [`Message`] to `writer` using the same length-prefixed
+    /// framing [`Self::read_async`] reads.
+    pub async fn write_async(&self, writer: &mut (impl AsyncWrite + Unpin)) -> io::Result<()> {
+        let payload = serde_json::to_vec(self).expect("Message serialization cannot fail");
+        writer.write_all(&(payload.len() as u32).to_be_bytes()).await?;
+        writer.write_all(&payload).await?;
+        Ok(())
+    }
+
+    fn string_array(value: Option<&serde_json::Value>) -> Vec<String> {
+        value
+            .and_then(serde_json::Value::as_array)
+            .map(|items| items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Message {
+    /// Serialization of this struct can't fail, so this never falls back
+    /// to an empty/placeholder string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(self).expect("Message serialization cannot fail"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Mutex;
+
+    /// Serializes the debug-logging tests below against each other, since
+    /// [`DEBUG_PARSE_FAILURES`] is a single process-wide flag and tests run
+    /// concurrently. A `tokio::sync::Mutex` rather than `std::sync::Mutex`
+    /// since one of those tests holds the guard across an `.await`.
+    static DEBUG_FLAG_LOCK: Mutex<()> = Mutex::const_new(());
+
+    #[test]
+    fn setters_mutate_the_serialized_form() {
+        let mut message = Message::new("ping", MessageType::Request, "runner0", "communicator", vec![]);
+        message.set_sender("runner1");
+        message.set_receiver("client0");
+        message.set_id(42);
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["sender"], "runner1");
+        assert_eq!(json["receiver"], "client0");
+        assert_eq!(json["id"], 42);
+    }
+
+    #[test]
+    fn named_constructors_set_the_correct_message_type() {
+        assert_eq!(
+            Message::request("ping", "a", "b", vec![]).message_type(),
+            MessageType::Request
+        );
+        assert_eq!(
+            Message::response("ping", "a", "b", vec![]).message_type(),
+            MessageType::Response
+        );
+        assert_eq!(
+            Message::event("ping", "a", "b", vec![]).message_type(),
+            MessageType::Event
+        );
+        assert_eq!(
+            Message::error("ping", "a", "b", vec![]).message_type(),
+            MessageType::Error
+        );
+    }
+
+    #[test]
+    fn args_len_and_has_args_reflect_the_arg_count() {
+        let none = Message::request("ping", "a", "b", vec![]);
+        assert_eq!(none.args_len(), 0);
+        assert!(!none.has_args());
+
+        let one = Message::request("ping", "a", "b", vec!["lobby".into()]);
+        assert_eq!(one.args_len(), 1);
+        assert!(one.has_args());
+
+        let many = Message::request("ping", "a", "b", vec!["a".into(), "b".into(), "c".into()]);
+        assert_eq!(many.args_len(), 3);
+        assert!(many.has_args());
+    }
+
+    #[test]
+    fn with_star_builders_are_fluent() {
+        let message = Message::new("ping", MessageType::Request, "runner0", "", vec![])
+            .with_receiver("client0")
+            .with_id(7);
+
+        assert_eq!(message.receiver(), "client0");
+        assert_eq!(message.id(), 7);
+    }
+
+    #[test]
+    fn jsonrpc_round_trips_a_request() {
+        let message = Message::request("ping", "runner0", "communicator", vec!["a".into(), "b".into()]);
+
+        let envelope = message.to_jsonrpc();
+        assert_eq!(envelope["method"], "ping");
+        assert_eq!(envelope["params"], serde_json::json!(["a", "b"]));
+
+        let round_tripped = Message::from_jsonrpc(&envelope).unwrap();
+        assert_eq!(round_tripped, message);
+    }
+
+    #[test]
+    fn jsonrpc_round_trips_an_error() {
+        let message = Message::error("ping", "communicator", "runner0", vec!["no such server".into()]);
+
+        let envelope = message.to_jsonrpc();
+        assert_eq!(envelope["error"]["message"], "ping");
+        assert_eq!(envelope["error"]["data"], serde_json::json!(["no such server"]));
+
+        let round_tripped = Message::from_jsonrpc(&envelope).unwrap();
+        assert_eq!(round_tripped, message);
+    }
+
+    #[test]
+    fn from_jsonrpc_rejects_an_envelope_with_no_method_result_or_error() {
+        let envelope = serde_json::json!({ "jsonrpc": "2.0", "id": 1 });
+        assert!(matches!(Message::from_jsonrpc(&envelope), Err(MCManageError::InvalidMessage(_))));
+    }
+
+    #[test]
+    fn display_renders_valid_json_matching_to_json_string() {
+        let message = Message::request("ping", "runner0", "communicator", vec!["a".into()]);
+
+        let displayed = format!("{message}");
+        let parsed: serde_json::Value = serde_json::from_str(&displayed).unwrap();
+        assert_eq!(parsed["command"], "ping");
+        assert_eq!(displayed, message.to_json_string());
+    }
+
+    #[tokio::test]
+    async fn write_async_and_read_async_round_trip_through_a_duplex_pair() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let message = Message::request("ping", "runner0", "communicator", vec!["a".into(), "b".into()]);
+
+        message.write_async(&mut client).await.unwrap();
+        let read_back = Message::read_async(&mut server).await.unwrap();
+
+        assert_eq!(read_back, Some(message));
+    }
+
+    #[tokio::test]
+    async fn read_async_returns_none_once_the_writer_closes_cleanly() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        drop(client);
+
+        let read_back = Message::read_async(&mut server).await.unwrap();
+        assert_eq!(read_back, None);
+    }
+
+    #[tokio::test]
+    async fn read_async_reassembles_a_message_delivered_across_several_partial_reads() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let message = Message::request("ping", "runner0", "communicator", vec!["a".into()]);
+        let payload = serde_json::to_vec(&message).unwrap();
+        let mut framed = (payload.len() as u32).to_be_bytes().to_vec();
+        framed.extend_from_slice(&payload);
+
+        let read = tokio::spawn(async move { Message::read_async(&mut server).await });
+
+        for chunk in framed.chunks(3) {
+            client.write_all(chunk).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+
+        let read_back = read.await.unwrap().unwrap();
+        assert_eq!(read_back, Some(message));
+    }
+
+    #[tokio::test]
+    async fn read_async_errors_on_a_clean_close_mid_message() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        // Announce an 8-byte payload but only ever send 2, then close.
+        client.write_all(&8u32.to_be_bytes()).await.unwrap();
+        client.write_all(b"ab").await.unwrap();
+        drop(client);
+
+        let result = Message::read_async(&mut server).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn parse_failure_diagnostic_is_none_when_debug_logging_is_disabled() {
+        let _guard = DEBUG_FLAG_LOCK.lock().await;
+        set_debug_parse_failures(false);
+
+        let err = serde_json::from_slice::<Message>(b"not json").unwrap_err();
+        assert!(Message::parse_failure_diagnostic(b"not json", &err).is_none());
+    }
+
+    #[tokio::test]
+    async fn parse_failure_diagnostic_includes_the_hex_bytes_and_error_when_enabled() {
+        let _guard = DEBUG_FLAG_LOCK.lock().await;
+        set_debug_parse_failures(true);
+
+        let payload = b"not json";
+        let err = serde_json::from_slice::<Message>(payload).unwrap_err();
+        let diagnostic = Message::parse_failure_diagnostic(payload, &err).unwrap();
+
+        assert!(diagnostic.contains(&Message::hex_preview(payload)));
+        assert!(diagnostic.contains(&err.to_string()));
+
+        set_debug_parse_failures(false);
+    }
+
+    #[tokio::test]
+    async fn read_async_still_errors_on_malformed_payloads_with_debug_logging_enabled() {
+        let _guard = DEBUG_FLAG_LOCK.lock().await;
+        set_debug_parse_failures(true);
+
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let payload = b"not json";
+        client.write_all(&(payload.len() as u32).to_be_bytes()).await.unwrap();
+        client.write_all(payload).await.unwrap();
+
+        let result = Message::read_async(&mut server).await;
+        assert!(result.is_err());
+
+        set_debug_parse_failures(false);
+    }
+}
@@ -0,0 +1,149 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+
+/// Abstracts over wall-clock time and sleeping, so time-based logic (e.g.
+/// [`MCServerManager::warn_and_await_idle_shutdown`](crate::MCServerManager::warn_and_await_idle_shutdown)'s
+/// idle-shutdown polling and [`MCServerManager::restart_all`](crate::MCServerManager::restart_all)'s
+/// jitter delay) can be driven deterministically by [`MockClock`] in tests
+/// instead of waiting on real time.
+///
+/// Boxes its returned future rather than using `async fn` so the trait stays
+/// object-safe: implementors are stored behind `Arc<dyn Clock>`.
+pub trait Clock: Send + Sync {
+    /// The current instant, as far as this clock is concerned.
+    fn now(&self) -> Instant;
+
+    /// Waits until `duration` has passed according to this clock.
+    fn sleep<'a>(&'a self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+}
+
+/// The default [`Clock`], backed by the real wall clock and `tokio::time`.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep<'a>(&'a self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A [`Clock`] whose passage of time is driven entirely by calls to
+/// [`Self::advance`], for deterministic tests of idle-shutdown/restart-timer
+/// logic without any real waiting.
+///
+/// `std::time::Instant` has no public constructor for an arbitrary point in
+/// time, so [`Self::now`] is synthesized as a fixed base instant (taken at
+/// construction) plus however much virtual time has been advanced since.
+pub struct MockClock {
+    base: Instant,
+    advanced: Mutex<Duration>,
+    notify: Notify,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            base: Instant::now(),
+            advanced: Mutex::new(Duration::ZERO),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Moves this clock's notion of "now" forward by `duration`, waking up
+    /// every [`Clock::sleep`] call whose deadline that reaches or passes.
+    pub fn advance(&self, duration: Duration) {
+        let mut advanced = self.advanced.lock().expect("mock clock mutex poisoned");
+        *advanced += duration;
+        drop(advanced);
+        self.notify.notify_waiters();
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.base + *self.advanced.lock().expect("mock clock mutex poisoned")
+    }
+
+    fn sleep<'a>(&'a self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        let deadline = self.now() + duration;
+        Box::pin(async move {
+            loop {
+                // Subscribe before checking the deadline so an `advance`
+                // landing between the check and the `.await` below isn't
+                // missed.
+                let notified = self.notify.notified();
+                if self.now() >= deadline {
+                    return;
+                }
+                notified.await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn system_clock_now_advances_with_real_time() {
+        let clock = SystemClock;
+        let first = clock.now();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(clock.now() > first);
+    }
+
+    #[tokio::test]
+    async fn system_clock_sleep_waits_for_the_real_duration() {
+        let clock = SystemClock;
+        let start = Instant::now();
+        clock.sleep(Duration::from_millis(20)).await;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn mock_clock_now_only_moves_when_advanced() {
+        let clock = MockClock::new();
+        let initial = clock.now();
+        assert_eq!(clock.now(), initial);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(clock.now(), initial + Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn mock_clock_sleep_resolves_once_enough_time_is_advanced() {
+        let clock = Arc::new(MockClock::new());
+        let waiter_clock = Arc::clone(&clock);
+        let waiter = tokio::spawn(async move { waiter_clock.sleep(Duration::from_secs(5)).await });
+
+        // Let the spawned task reach its first poll (and compute its
+        // deadline) before advancing the clock at all.
+        tokio::task::yield_now().await;
+
+        // Not enough time has passed yet, so the sleep should still be
+        // pending after a few unrelated partial advances.
+        clock.advance(Duration::from_secs(2));
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        clock.advance(Duration::from_secs(3));
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("sleep should resolve once the deadline has passed")
+            .unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+/// The subset of a running child process [`MCServer`](crate::MCServer)
+/// needs to drive it: writing lines to stdin, reading lines from stdout,
+/// and stopping it. Abstracted behind [`CommandRunner`] so tests can inject
+/// a canned fake that emits scripted lines instead of actually spawning an
+/// OS process, without touching the startup/stop/escalation logic that
+/// drives it.
+pub trait ManagedProcess: Send {
+    /// The OS process id, if the process is still running and has one.
+    /// Used only to deliver unix signals; a fake process can always return
+    /// `None`.
+    fn id(&self) -> Option<u32>;
+
+    /// Releases this handle's ownership of the process without killing it:
+    /// kill-on-drop no longer applies, and [`Self::kill`]/[`Self::wait`]
+    /// become no-ops afterwards. Returns the pid it had, if any, so a
+    /// caller (see [`MCServer::detach`](crate::MCServer::detach)) can
+    /// record it before letting this handle go.
+    fn detach(&mut self) -> Option<u32>;
+
+    /// Sends `line` (with a trailing newline) to the process's stdin.
+    fn write_line<'a>(&'a mut self, line: &'a str) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'a>>;
+
+    /// Reads the next line from the process's stdout, or `Ok(None)` once
+    /// it's closed.
+    fn next_line(&mut self) -> Pin<Box<dyn Future<Output = io::Result<Option<String>>> + Send + '_>>;
+
+    /// Forcibly kills the process.
+    fn kill(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>>;
+
+    /// Waits for the process to exit on its own.
+    fn wait(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>>;
+}
+
+/// Spawns the [`ManagedProcess`] a [`MCServer`](crate::MCServer) drives.
+/// Injected via [`MCServer::with_command_runner`](crate::MCServer::with_command_runner),
+/// defaulting to [`SystemCommandRunner`] (a real OS process). Tests inject
+/// a fake implementation to exercise start/join/leave/stop parsing without
+/// spawning anything.
+pub trait CommandRunner: Send + Sync {
+    fn spawn(
+        &self,
+        command: &str,
+        args: &[String],
+        envs: &HashMap<String, String>,
+        current_dir: &Path,
+        kill_on_drop: bool,
+    ) -> io::Result<Box<dyn ManagedProcess>>;
+}
+
+/// The production [`CommandRunner`]: spawns a real OS process via
+/// [`tokio::process::Command`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn spawn(
+        &self,
+        command: &str,
+        args: &[String],
+        envs: &HashMap<String, String>,
+        current_dir: &Path,
+        kill_on_drop: bool,
+    ) -> io::Result<Box<dyn ManagedProcess>> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(envs)
+            .current_dir(current_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(kill_on_drop)
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin was piped");
+        let stdout = child.stdout.take().expect("stdout was piped");
+        Ok(Box::new(SystemProcess {
+            child: Some(child),
+            stdin,
+            lines: BufReader::new(stdout).lines(),
+        }))
+    }
+}
+
+struct SystemProcess {
+    /// `None` once [`Self::detach`] has released it; every other method
+    /// treats that as "no process to act on" rather than panicking, since a
+    /// detached handle is expected to be dropped shortly after.
+    child: Option<tokio::process::Child>,
+    stdin: tokio::process::ChildStdin,
+    lines: tokio::io::Lines<BufReader<tokio::process::ChildStdout>>,
+}
+
+impl ManagedProcess for SystemProcess {
+    fn id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(|child| child.id())
+    }
+
+    fn detach(&mut self) -> Option<u32> {
+        let child = self.child.take()?;
+        let pid = child.id();
+        // Leaking the handle instead of dropping it skips `Child`'s own
+        // kill-on-drop check entirely, so the process keeps running under
+        // the OS regardless of how it was spawned.
+        std::mem::forget(child);
+        pid
+    }
+
+    fn write_line<'a>(&'a mut self, line: &'a str) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.stdin.write_all(line.as_bytes()).await?;
+            self.stdin.write_all(b"\n").await?;
+            self.stdin.flush().await
+        })
+    }
+
+    fn next_line(&mut self) -> Pin<Box<dyn Future<Output = io::Result<Option<String>>> + Send + '_>> {
+        Box::pin(self.lines.next_line())
+    }
+
+    fn kill(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            match self.child.as_mut() {
+                Some(child) => child.kill().await,
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn wait(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            match self.child.as_mut() {
+                Some(child) => {
+                    child.wait().await?;
+                    Ok(())
+                }
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+/// A process this program didn't spawn itself, reattached by pid after a
+/// restart; see [`crate::MCServerManager::attach`]. There's no stdin/stdout
+/// pipe to reopen for an already-running external process, so this only
+/// supports liveness checks and signal delivery: [`Self::write_line`] and
+/// [`Self::next_line`] report it as unreachable rather than blocking
+/// forever on input/output that will never arrive.
+#[cfg(unix)]
+pub struct AttachedProcess {
+    pid: u32,
+    /// Set by [`ManagedProcess::detach`]; from then on this handle no
+    /// longer reports, signals or waits on `pid`.
+    detached: bool,
+}
+
+#[cfg(unix)]
+impl AttachedProcess {
+    pub fn new(pid: u32) -> Self {
+        Self { pid, detached: false }
+    }
+
+    /// Probes whether `pid` still refers to a live process, by sending it
+    /// signal `0` (which has no effect beyond the existence/permission
+    /// check `kill(2)` performs for every signal).
+    fn is_alive(&self) -> bool {
+        // SAFETY: `kill` only reads `pid`/`signal` and has no
+        // memory-safety preconditions beyond being a valid syscall.
+        unsafe { libc::kill(self.pid as libc::pid_t, 0) == 0 }
+    }
+}
+
+#[cfg(unix)]
+impl ManagedProcess for AttachedProcess {
+    fn id(&self) -> Option<u32> {
+        (!self.detached && self.is_alive()).then_some(self.pid)
+    }
+
+    fn detach(&mut self) -> Option<u32> {
+        self.detached = true;
+        Some(self.pid)
+    }
+
+    fn write_line<'a>(&'a mut self, _line: &'a str) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no stdin pipe is available for an attached process",
+            ))
+        })
+    }
+
+    fn next_line(&mut self) -> Pin<Box<dyn Future<Output = io::Result<Option<String>>> + Send + '_>> {
+        Box::pin(async move { Ok(None) })
+    }
+
+    fn kill(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            if !self.detached {
+                // SAFETY: see `is_alive`.
+                unsafe { libc::kill(self.pid as libc::pid_t, libc::SIGKILL) };
+            }
+            Ok(())
+        })
+    }
+
+    fn wait(&mut self) -> Pin<Box<dyn Future<Output = io::Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            while !self.detached && self.is_alive() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Ok(())
+        })
+    }
+}
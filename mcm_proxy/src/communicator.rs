@@ -0,0 +1,1045 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use mcm_misc::message::Message;
+use mcm_misc::{Config, MCManageError};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, Mutex, Notify};
+use tokio::task::JoinHandle;
+
+use crate::handler_id::{ClientKind, HandlerId};
+
+/// Writes a single [`Message`] to `stream` using a 4-byte big-endian
+/// length prefix followed by its JSON body, returning the number of
+/// payload bytes written (for metrics bookkeeping).
+pub(crate) async fn write_message(stream: &mut TcpStream, message: &Message) -> Result<usize, MCManageError> {
+    let payload = serde_json::to_vec(message).expect("Message serialization cannot fail");
+    stream.write_all(&(payload.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&payload).await?;
+    Ok(payload.len())
+}
+
+/// Reads a single [`Message`] off `stream`, returning it along with the
+/// number of payload bytes read.
+pub(crate) async fn read_message(stream: &mut TcpStream) -> Result<(Message, usize), MCManageError> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf).await?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    let message = serde_json::from_slice(&payload)
+        .map_err(|err| MCManageError::InvalidFile(PathBuf::from("<message>"), err.to_string()))?;
+    Ok((message, len))
+}
+
+/// Checks whether `message`'s [`Message::id`] is the next expected one
+/// from its sender, treating `id` as a per-sender monotonically
+/// increasing sequence number (e.g. assigned by a runner on send).
+///
+/// Over a reconnect or with retries, messages can arrive out of order or
+/// with a gap. An out-of-order id (less than or equal to the highest
+/// already seen from that sender) or a gap (more than one past it) is
+/// logged as a warning rather than treated as an error, since `id` is
+/// optional and a sender that never uses it (always `0`) must not be
+/// flagged on every single message. Returns a `resync_request` request
+/// addressed back to the sender if `strict_ordering` is enabled and a gap
+/// (not just an out-of-order id) was detected, for the caller to send
+/// back over the connection.
+pub(crate) async fn check_order(
+    last_seq: &Mutex<HashMap<String, u64>>,
+    strict_ordering: bool,
+    message: &Message,
+) -> Option<Message> {
+    let sender = message.sender();
+    let seq = message.id();
+    let mut last_seq = last_seq.lock().await;
+
+    let resync = match last_seq.get(sender) {
+        Some(&previous) if seq <= previous => {
+            eprintln!("out-of-order message from '{sender}': got id {seq}, already saw {previous}");
+            None
+        }
+        Some(&previous) if seq > previous + 1 => {
+            eprintln!("gap in messages from '{sender}': expected id {}, got {seq}", previous + 1);
+            strict_ordering.then(|| Message::request("resync_request", "communicator", sender, vec![]))
+        }
+        _ => None,
+    };
+
+    if seq > last_seq.get(sender).copied().unwrap_or(0) {
+        last_seq.insert(sender.to_string(), seq);
+    }
+
+    resync
+}
+
+/// A point-in-time snapshot of message/byte counters, either for a single
+/// handler or aggregated across all of them; see [`Communicator::metrics`]
+/// and [`Communicator::handler_metrics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommunicatorMetrics {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+#[derive(Debug, Default)]
+struct HandlerMetrics {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl HandlerMetrics {
+    fn record_sent(&self, bytes: usize) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn record_received(&self, bytes: usize) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> CommunicatorMetrics {
+        CommunicatorMetrics {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+type HandlerEntry = (JoinHandle<()>, Arc<HandlerMetrics>, Arc<Notify>);
+
+/// The message-protocol version this Communicator speaks, advertised to
+/// every connecting peer during the handshake performed in
+/// [`Communicator::spawn_handler`]; see [`MIN_PROTOCOL_VERSION`].
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The oldest client protocol version a handshake still accepts. A client
+/// advertising an older version is rejected outright rather than
+/// negotiated down to, since there's no guarantee it understands messages
+/// shaped for anything before it was written.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Parses the protocol version out of a handshake reply (the first message
+/// read from a newly accepted connection, before it's treated as
+/// application traffic; see [`Communicator::spawn_handler`]), negotiating
+/// down to the lower of it and this Communicator's own [`PROTOCOL_VERSION`]
+/// so a compatible-but-older peer can still be served with whatever
+/// features it understands.
+///
+/// Returns [`MCManageError::InvalidMessage`] if the reply doesn't carry a
+/// version or the version is below [`MIN_PROTOCOL_VERSION`], rather than
+/// silently forwarding messages an incompatible peer can't parse.
+fn negotiated_version(reply: &Message) -> Result<u32, MCManageError> {
+    let peer_version: u32 = reply
+        .args()
+        .first()
+        .and_then(|arg| arg.parse().ok())
+        .ok_or_else(|| MCManageError::InvalidMessage("handshake reply did not include a protocol version".to_string()))?;
+
+    if peer_version < MIN_PROTOCOL_VERSION {
+        return Err(MCManageError::InvalidMessage(format!(
+            "peer protocol version {peer_version} is older than the minimum supported version {MIN_PROTOCOL_VERSION}"
+        )));
+    }
+
+    Ok(peer_version.min(PROTOCOL_VERSION))
+}
+
+/// Accepts connections from runners/clients and keeps one handler task per
+/// connection alive for as long as the Communicator is running.
+///
+/// Every lock here is a [`tokio::sync::Mutex`], not a `std::sync::Mutex`
+/// with manual poison handling — holding one across an `.await` (e.g. while
+/// draining [`Self::handlers`] in [`Self::stop`]) would otherwise block the
+/// whole runtime thread instead of just the task waiting on it.
+pub struct Communicator {
+    config: Arc<Config>,
+    handlers: Arc<Mutex<HashMap<HandlerId, HandlerEntry>>>,
+    shutdown: broadcast::Sender<()>,
+    next_runner_index: AtomicU32,
+    next_client_index: AtomicU32,
+    /// How many recently-seen `(sender, id)` pairs are remembered to drop
+    /// retried/duplicate messages before forwarding; `0` disables dedup.
+    dedup_window: usize,
+    recently_seen: Mutex<VecDeque<(String, u64)>>,
+    aggregate_metrics: Arc<HandlerMetrics>,
+    /// Every handler forwards the messages it reads into this bounded
+    /// channel (capacity from `Config::inbound_queue_capacity`) rather than
+    /// an unbounded one, so a slow consumer (taken via
+    /// [`Self::take_inbound_receiver`]) makes the channel fill up and the
+    /// handler pause reading further messages, instead of messages
+    /// buffering in memory without limit.
+    inbound: mpsc::Sender<Message>,
+    inbound_rx: Mutex<Option<mpsc::Receiver<Message>>>,
+    /// The highest [`Message::id`] seen from each sender (by
+    /// [`Message::sender`]), treating `id` as a per-sender monotonically
+    /// increasing sequence number; see [`Self::with_strict_ordering`] and
+    /// [`check_order`].
+    last_seq: Arc<Mutex<HashMap<String, u64>>>,
+    /// Whether a detected gap in a sender's sequence makes its handler
+    /// send a `resync_request` request back, rather than just logging a
+    /// warning; see [`Self::with_strict_ordering`].
+    strict_ordering: bool,
+    /// Which [`ClientKind`]s make an unexpected disconnect (the socket
+    /// erroring or closing, as opposed to a deliberate [`Self::disconnect`]
+    /// call) stop the whole Communicator instead of the default of leaving
+    /// every other handler connected; see [`Self::with_stop_on_disconnect`].
+    stop_on_disconnect: HashSet<ClientKind>,
+    /// The protocol version negotiated with each currently connected
+    /// handler via [`negotiated_version`], so forwarding logic can
+    /// downgrade features for an older peer; see [`Self::handler_version`].
+    versions: Arc<Mutex<HashMap<HandlerId, u32>>>,
+    /// The address most recently bound for each [`ClientKind`] via
+    /// [`Self::listen`], so [`Self::self_test`] has somewhere to connect
+    /// back to.
+    listen_addrs: Mutex<HashMap<ClientKind, SocketAddr>>,
+}
+
+impl Communicator {
+    pub fn new(config: Arc<Config>) -> Self {
+        let (shutdown, _) = broadcast::channel(1);
+        let (inbound, inbound_rx) = mpsc::channel(config.inbound_queue_capacity);
+        Self {
+            config,
+            handlers: Arc::new(Mutex::new(HashMap::new())),
+            shutdown,
+            next_runner_index: AtomicU32::new(0),
+            next_client_index: AtomicU32::new(0),
+            dedup_window: 0,
+            recently_seen: Mutex::new(VecDeque::new()),
+            aggregate_metrics: Arc::new(HandlerMetrics::default()),
+            inbound,
+            inbound_rx: Mutex::new(Some(inbound_rx)),
+            last_seq: Arc::new(Mutex::new(HashMap::new())),
+            strict_ordering: false,
+            stop_on_disconnect: HashSet::new(),
+            versions: Arc::new(Mutex::new(HashMap::new())),
+            listen_addrs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Enables requesting a resync from a sender whose messages arrive
+    /// with a gap in their per-sender sequence (see [`Message::id`]),
+    /// instead of just logging a warning and otherwise delivering them
+    /// unchanged.
+    pub fn with_strict_ordering(mut self, strict_ordering: bool) -> Self {
+        self.strict_ordering = strict_ordering;
+        self
+    }
+
+    /// Enables dropping duplicate messages (by `(sender, id)`) seen again
+    /// within the last `dedup_window` messages. Pass `0` to disable dedup.
+    pub fn with_dedup_window(mut self, dedup_window: usize) -> Self {
+        self.dedup_window = dedup_window;
+        self
+    }
+
+    /// Configures `kind` connections so that an unexpected disconnect (the
+    /// socket erroring or closing on its own, as opposed to a deliberate
+    /// [`Self::disconnect`] call) calls [`Self::stop`] and takes the whole
+    /// Communicator down with it, rather than the default of leaving every
+    /// other handler connected and just dropping messages addressed to the
+    /// one that disconnected.
+    pub fn with_stop_on_disconnect(mut self, kind: ClientKind) -> Self {
+        self.stop_on_disconnect.insert(kind);
+        self
+    }
+
+    /// Returns whether `message` should be forwarded, dropping it if it's a
+    /// duplicate (by `(sender, id)`) of one seen within the configured
+    /// dedup window. Always forwards when dedup is disabled.
+    pub async fn should_forward(&self, message: &Message) -> bool {
+        if self.dedup_window == 0 {
+            return true;
+        }
+
+        let key = (message.sender().to_string(), message.id());
+        let mut recently_seen = self.recently_seen.lock().await;
+        if recently_seen.contains(&key) {
+            return false;
+        }
+
+        recently_seen.push_back(key);
+        while recently_seen.len() > self.dedup_window {
+            recently_seen.pop_front();
+        }
+        true
+    }
+
+    /// Returns the aggregate message/byte counters across every handler
+    /// that has ever been spawned (including ones that have since closed).
+    pub async fn metrics(&self) -> CommunicatorMetrics {
+        self.aggregate_metrics.snapshot()
+    }
+
+    /// Returns the message/byte counters for a single handler, or `None`
+    /// if no handler with that id is currently tracked.
+    pub async fn handler_metrics(&self, id: HandlerId) -> Option<CommunicatorMetrics> {
+        self.handlers.lock().await.get(&id).map(|(_, metrics, _)| metrics.snapshot())
+    }
+
+    /// Returns the protocol version negotiated with `id` during its
+    /// handshake, or `None` if no handler with that id is currently
+    /// connected.
+    pub async fn handler_version(&self, id: HandlerId) -> Option<u32> {
+        self.versions.lock().await.get(&id).copied()
+    }
+
+    /// Gracefully closes the connection belonging to `handler_id` (e.g. to
+    /// kick a specific connected client/runner), signalling its handler to
+    /// send a final `server_shutdown` event and close its socket, then
+    /// deregisters it from the Communicator.
+    ///
+    /// Returns [`MCManageError::InvalidHandlerId`] if `handler_id` isn't a
+    /// valid id, or [`MCManageError::NotFound`] if no handler with that id
+    /// is currently connected.
+    pub async fn disconnect(&self, handler_id: &str) -> Result<(), MCManageError> {
+        let id: HandlerId = handler_id.parse()?;
+
+        let (task, _, notify) = self.handlers.lock().await.remove(&id).ok_or(MCManageError::NotFound)?;
+        self.versions.lock().await.remove(&id);
+        notify.notify_one();
+        let _ = task.await;
+        Ok(())
+    }
+
+    /// Takes ownership of the receiving half of the bounded channel every
+    /// handler forwards received messages into, for a consumer to process
+    /// them. Can only be taken once; returns `None` on a second call.
+    pub async fn take_inbound_receiver(&self) -> Option<mpsc::Receiver<Message>> {
+        self.inbound_rx.lock().await.take()
+    }
+
+    /// Health-checks the `kind` listener by opening a loopback connection to
+    /// it, completing the handshake as a throwaway client, and disconnecting
+    /// — confirming the accept loop and registration path are still
+    /// functioning, for callers that want a cheap liveness probe rather than
+    /// inspecting connected handlers directly.
+    ///
+    /// Returns [`MCManageError::NotReady`] if no listener of `kind` is
+    /// currently bound (e.g. [`Self::listen`] was never called for it, or
+    /// the Communicator has been stopped and a fresh one hasn't started
+    /// listening yet).
+    ///
+    /// Closing the throwaway connection afterwards looks like an unexpected
+    /// disconnect to its handler, same as any other client going away
+    /// without a deliberate [`Self::disconnect`] call; avoid calling this
+    /// for a `kind` configured via [`Self::with_stop_on_disconnect`], since
+    /// it would stop the whole Communicator.
+    pub async fn self_test(&self, kind: ClientKind) -> Result<(), MCManageError> {
+        let addr = self.listen_addrs.lock().await.get(&kind).copied().ok_or_else(|| {
+            MCManageError::NotReady(format!("no '{kind:?}' listener is currently bound"))
+        })?;
+
+        let mut stream = TcpStream::connect(addr).await?;
+        let _advertise = read_message(&mut stream).await?;
+        let reply = Message::event("handshake", "self-test", "communicator", vec![PROTOCOL_VERSION.to_string()]);
+        write_message(&mut stream, &reply).await?;
+        Ok(())
+    }
+
+    /// Like [`Self::listen`], but binds `Config::bind_address` instead of
+    /// taking an explicit address, for the common case of a single
+    /// Communicator listening on its configured default.
+    pub async fn listen_configured(self: &Arc<Self>, kind: ClientKind) -> Result<SocketAddr, MCManageError> {
+        self.listen(self.config.bind_address, kind).await
+    }
+
+    /// Binds `addr` and starts accepting connections of `kind` in the
+    /// background, returning the address actually bound to.
+    ///
+    /// If the bind fails (e.g. the port is already in use), it's retried up
+    /// to `Config::bind_retries` more times, waiting `Config::bind_retry_delay`
+    /// between attempts, before giving up with the last error.
+    pub async fn listen(self: &Arc<Self>, addr: SocketAddr, kind: ClientKind) -> Result<SocketAddr, MCManageError> {
+        let mut attempt = 0;
+        let listener = loop {
+            match TcpListener::bind(addr).await {
+                Ok(listener) => break listener,
+                Err(err) if attempt < self.config.bind_retries => {
+                    attempt += 1;
+                    eprintln!(
+                        "could not bind {addr} (attempt {attempt}/{}): {err}",
+                        self.config.bind_retries
+                    );
+                    tokio::time::sleep(self.config.bind_retry_delay).await;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        };
+        let local_addr = listener.local_addr()?;
+        self.listen_addrs.lock().await.insert(kind, local_addr);
+
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                this.spawn_handler(kind, stream).await;
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    async fn spawn_handler(self: &Arc<Self>, kind: ClientKind, mut stream: TcpStream) -> HandlerId {
+        let index = match kind {
+            ClientKind::Runner => self.next_runner_index.fetch_add(1, Ordering::SeqCst),
+            ClientKind::Client => self.next_client_index.fetch_add(1, Ordering::SeqCst),
+        };
+        let id = HandlerId::new(kind, index);
+        let mut shutdown_rx = self.shutdown.subscribe();
+        let metrics = Arc::new(HandlerMetrics::default());
+        let aggregate = Arc::clone(&self.aggregate_metrics);
+        let handler_metrics = Arc::clone(&metrics);
+        let inbound = self.inbound.clone();
+        let disconnect = Arc::new(Notify::new());
+        let handler_disconnect = Arc::clone(&disconnect);
+        let last_seq = Arc::clone(&self.last_seq);
+        let strict_ordering = self.strict_ordering;
+        let stop_on_disconnect = self.stop_on_disconnect.contains(&kind);
+        let versions = Arc::clone(&self.versions);
+        let this = Arc::clone(self);
+
+        let task = tokio::spawn(async move {
+            let advertise = Message::event("handshake", "communicator", "", vec![PROTOCOL_VERSION.to_string()]);
+            if write_message(&mut stream, &advertise).await.is_err() {
+                return;
+            }
+
+            // The first message read is always the peer's handshake reply,
+            // not application traffic — `negotiated` tracks whether it's
+            // been consumed yet so the rest of the loop stays unchanged
+            // below.
+            let mut negotiated = false;
+            let mut unexpected_disconnect = false;
+            let mut handshake_rejected = false;
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = handler_disconnect.notified() => break,
+                    result = read_message(&mut stream) => match result {
+                        Ok((message, bytes)) => {
+                            if !negotiated {
+                                match negotiated_version(&message) {
+                                    Ok(version) => {
+                                        versions.lock().await.insert(id, version);
+                                        negotiated = true;
+                                        continue;
+                                    }
+                                    Err(err) => {
+                                        eprintln!("[{id}] handshake failed, closing the connection: {err}");
+                                        let rejection = Message::error(err.to_string(), "communicator", "", vec![]);
+                                        let _ = write_message(&mut stream, &rejection).await;
+                                        handshake_rejected = true;
+                                        break;
+                                    }
+                                }
+                            }
+
+                            handler_metrics.record_received(bytes);
+                            aggregate.record_received(bytes);
+
+                            if let Some(resync) = check_order(&last_seq, strict_ordering, &message).await {
+                                if let Ok(bytes) = write_message(&mut stream, &resync).await {
+                                    handler_metrics.record_sent(bytes);
+                                    aggregate.record_sent(bytes);
+                                }
+                            }
+
+                            // Blocks here, rather than reading the next
+                            // message, while the inbound channel is full —
+                            // backpressure instead of unbounded buffering.
+                            if inbound.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => {
+                            unexpected_disconnect = true;
+                            break;
+                        }
+                    },
+                }
+            }
+
+            versions.lock().await.remove(&id);
+
+            // A rejected handshake already told the peer why via its own
+            // error message; a further goodbye would just be noise on a
+            // connection that was never fully registered.
+            if !handshake_rejected {
+                let goodbye = Message::event("server_shutdown", "communicator", "", vec![]);
+                if let Ok(bytes) = write_message(&mut stream, &goodbye).await {
+                    handler_metrics.record_sent(bytes);
+                    aggregate.record_sent(bytes);
+                }
+            }
+            let _ = stream.shutdown().await;
+
+            // Resilient by default: an unexpected disconnect only takes
+            // down this handler, leaving every other connection untouched.
+            // `with_stop_on_disconnect` opts specific kinds out of that, for
+            // operators who'd rather fail the whole Communicator fast than
+            // keep running with e.g. their console gone. Spawned rather
+            // than awaited here, since `Communicator::stop` joins every
+            // handler task (including this one) and would otherwise wait on
+            // itself.
+            if unexpected_disconnect && stop_on_disconnect {
+                eprintln!("[{id}] disconnected unexpectedly; stopping the Communicator ({kind:?} is configured via with_stop_on_disconnect)");
+                tokio::spawn(async move { this.stop().await });
+            }
+        });
+
+        self.handlers.lock().await.insert(id, (task, metrics, disconnect));
+        id
+    }
+
+    /// Stops the Communicator, having every handler send a final
+    /// `server_shutdown` event before closing its socket, and waits for all
+    /// handlers to finish doing so.
+    pub async fn stop(&self) {
+        let _ = self.shutdown.send(());
+
+        let tasks: Vec<JoinHandle<()>> = self.handlers.lock().await.drain().map(|(_, (t, ..))| t).collect();
+        for task in tasks {
+            let _ = task.await;
+        }
+
+        // The accept loop spawned in `listen` keeps running (it isn't tied
+        // to `shutdown`), but as far as `self_test` is concerned a stopped
+        // Communicator has nothing worth health-checking, so forget every
+        // bound address.
+        self.listen_addrs.lock().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcm_misc::message::MessageType;
+    use std::time::Duration;
+    use tokio::net::TcpStream as ClientStream;
+    use tokio::time::timeout;
+
+    async fn read_message_raw(stream: &mut ClientStream) -> Message {
+        let mut len_buf = [0u8; 4];
+        stream.read_exact(&mut len_buf).await.unwrap();
+        let len = u32::from_be_bytes(len_buf) as usize;
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await.unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    async fn write_message_raw(stream: &mut ClientStream, message: &Message) -> usize {
+        let payload = serde_json::to_vec(message).unwrap();
+        stream.write_all(&(payload.len() as u32).to_be_bytes()).await.unwrap();
+        stream.write_all(&payload).await.unwrap();
+        payload.len()
+    }
+
+    fn test_config() -> Arc<Config> {
+        Arc::new(Config::new(std::env::temp_dir(), false))
+    }
+
+    /// Completes the client side of the handshake over a raw `stream`:
+    /// reads the Communicator's advertised version and replies with
+    /// `client_version`, returning the advertisement for tests that want to
+    /// assert on it. Every test below that exchanges application messages
+    /// over a socket must call this first, or its first write would be
+    /// consumed as the handshake reply instead of forwarded.
+    async fn handshake_as_client(stream: &mut ClientStream, client_version: u32) -> Message {
+        let advertise = read_message_raw(stream).await;
+        let reply = Message::event("handshake", "test-client", "communicator", vec![client_version.to_string()]);
+        write_message_raw(stream, &reply).await;
+        advertise
+    }
+
+    #[tokio::test]
+    async fn listen_configured_binds_the_configured_address() {
+        let config = Arc::new(Config::new(std::env::temp_dir(), false).with_bind_address("127.0.0.1:0".parse().unwrap()));
+        let communicator = Arc::new(Communicator::new(config));
+
+        let addr = communicator.listen_configured(ClientKind::Runner).await.unwrap();
+
+        let client = ClientStream::connect(addr).await;
+        assert!(client.is_ok());
+    }
+
+    #[tokio::test]
+    async fn handler_sends_goodbye_before_closing() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut client, PROTOCOL_VERSION).await;
+
+        // Give the accept loop a beat to register the handler.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        communicator.stop().await;
+
+        let goodbye = read_message_raw(&mut client).await;
+        assert_eq!(goodbye.message_type(), MessageType::Event);
+        assert_eq!(goodbye.command(), "server_shutdown");
+
+        let mut buf = [0u8; 1];
+        let n = client.read(&mut buf).await.unwrap();
+        assert_eq!(n, 0, "socket should be closed after the goodbye message");
+    }
+
+    #[tokio::test]
+    async fn disconnect_closes_the_named_handlers_socket_and_deregisters_it() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut client, PROTOCOL_VERSION).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        communicator.disconnect(&HandlerId::new(ClientKind::Runner, 0).to_string()).await.unwrap();
+
+        let goodbye = read_message_raw(&mut client).await;
+        assert_eq!(goodbye.message_type(), MessageType::Event);
+        assert_eq!(goodbye.command(), "server_shutdown");
+
+        let mut buf = [0u8; 1];
+        let n = client.read(&mut buf).await.unwrap();
+        assert_eq!(n, 0, "socket should be closed after the goodbye message");
+
+        assert!(communicator.handlers.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn disconnect_errors_for_an_unknown_handler_id() {
+        let communicator = Communicator::new(test_config());
+        let result = communicator.disconnect("r0").await;
+        assert!(matches!(result, Err(MCManageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn disconnect_errors_for_an_invalid_handler_id() {
+        let communicator = Communicator::new(test_config());
+        let result = communicator.disconnect("not-an-id").await;
+        assert!(matches!(result, Err(MCManageError::InvalidHandlerId(_))));
+    }
+
+    #[tokio::test]
+    async fn concurrent_start_stop_and_handler_registration_never_deadlocks() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+
+        // Spin up several listeners and connect a handful of clients to
+        // each concurrently, racing registration (which locks `handlers`)
+        // against a concurrent `stop` (which also locks `handlers` to
+        // drain it) and metrics reads.
+        let mut addrs = Vec::new();
+        for kind in [ClientKind::Runner, ClientKind::Client] {
+            let addr = communicator.listen("127.0.0.1:0".parse().unwrap(), kind).await.unwrap();
+            addrs.push(addr);
+        }
+
+        let mut connectors = Vec::new();
+        for &addr in &addrs {
+            for _ in 0..5 {
+                let communicator = Arc::clone(&communicator);
+                connectors.push(tokio::spawn(async move {
+                    let _client = ClientStream::connect(addr).await.unwrap();
+                    let _ = communicator.metrics().await;
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                }));
+            }
+        }
+
+        let stopper = {
+            let communicator = Arc::clone(&communicator);
+            tokio::spawn(async move { communicator.stop().await })
+        };
+
+        let result = timeout(Duration::from_secs(5), async {
+            for connector in connectors {
+                let _ = connector.await;
+            }
+            stopper.await.unwrap();
+        })
+        .await;
+
+        assert!(result.is_ok(), "start/stop/handler registration raced without completing — suspect a deadlock");
+    }
+
+    #[tokio::test]
+    async fn an_unexpected_disconnect_leaves_other_handlers_connected_by_default() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Client)
+            .await
+            .unwrap();
+
+        let console = ClientStream::connect(addr).await.unwrap();
+        let mut other = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut other, PROTOCOL_VERSION).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert_eq!(communicator.handlers.lock().await.len(), 2);
+
+        // Simulate the "console" connection dropping unexpectedly, as
+        // opposed to a deliberate `disconnect()` call.
+        drop(console);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // The other handler must still be alive and able to exchange
+        // messages, since `stop_on_disconnect` was never configured.
+        let ping = Message::request("ping", "other", "communicator", vec![]);
+        write_message_raw(&mut other, &ping).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(
+            !communicator.handlers.lock().await.is_empty(),
+            "the surviving handler should still be registered"
+        );
+
+        communicator.stop().await;
+        let goodbye = read_message_raw(&mut other).await;
+        assert_eq!(goodbye.command(), "server_shutdown");
+    }
+
+    #[tokio::test]
+    async fn with_stop_on_disconnect_stops_every_handler_once_the_configured_kind_drops() {
+        let communicator = Arc::new(Communicator::new(test_config()).with_stop_on_disconnect(ClientKind::Client));
+        let console_addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Client)
+            .await
+            .unwrap();
+        let runner_addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let console = ClientStream::connect(console_addr).await.unwrap();
+        let mut runner = ClientStream::connect(runner_addr).await.unwrap();
+        handshake_as_client(&mut runner, PROTOCOL_VERSION).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        // An unexpected drop of the configured (`Client`) kind must take
+        // the whole Communicator down, so even the unrelated runner
+        // handler gets its goodbye and closes.
+        drop(console);
+
+        let goodbye = read_message_raw(&mut runner).await;
+        assert_eq!(goodbye.command(), "server_shutdown");
+
+        let mut buf = [0u8; 1];
+        let n = runner.read(&mut buf).await.unwrap();
+        assert_eq!(n, 0, "the runner's socket should be closed once stop_on_disconnect fires");
+    }
+
+    #[tokio::test]
+    async fn check_order_is_silent_for_in_order_messages() {
+        let last_seq = Mutex::new(HashMap::new());
+        let first = Message::request("ping", "runner0", "communicator", vec![]).with_id(1);
+        let second = Message::request("ping", "runner0", "communicator", vec![]).with_id(2);
+
+        assert!(check_order(&last_seq, false, &first).await.is_none());
+        assert!(check_order(&last_seq, false, &second).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn check_order_detects_a_gap_but_does_not_resync_outside_strict_mode() {
+        let last_seq = Mutex::new(HashMap::new());
+        let first = Message::request("ping", "runner0", "communicator", vec![]).with_id(1);
+        let skipped_ahead = Message::request("ping", "runner0", "communicator", vec![]).with_id(5);
+
+        check_order(&last_seq, false, &first).await;
+        assert!(check_order(&last_seq, false, &skipped_ahead).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn check_order_requests_a_resync_on_a_gap_in_strict_mode() {
+        let last_seq = Mutex::new(HashMap::new());
+        let first = Message::request("ping", "runner0", "communicator", vec![]).with_id(1);
+        let skipped_ahead = Message::request("ping", "runner0", "communicator", vec![]).with_id(5);
+
+        check_order(&last_seq, true, &first).await;
+        let resync = check_order(&last_seq, true, &skipped_ahead).await.unwrap();
+        assert_eq!(resync.command(), "resync_request");
+        assert_eq!(resync.receiver(), "runner0");
+    }
+
+    #[tokio::test]
+    async fn check_order_does_not_resync_for_an_out_of_order_but_non_gapped_id() {
+        let last_seq = Mutex::new(HashMap::new());
+        let first = Message::request("ping", "runner0", "communicator", vec![]).with_id(2);
+        let delayed_retry = Message::request("ping", "runner0", "communicator", vec![]).with_id(1);
+
+        check_order(&last_seq, true, &first).await;
+        assert!(check_order(&last_seq, true, &delayed_retry).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn a_handler_sends_a_resync_request_when_strict_ordering_detects_a_gap() {
+        let communicator = Arc::new(Communicator::new(test_config()).with_strict_ordering(true));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut client, PROTOCOL_VERSION).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        write_message_raw(
+            &mut client,
+            &Message::request("ping", "runner0", "communicator", vec![]).with_id(1),
+        )
+        .await;
+        write_message_raw(
+            &mut client,
+            &Message::request("ping", "runner0", "communicator", vec![]).with_id(5),
+        )
+        .await;
+
+        let resync = read_message_raw(&mut client).await;
+        assert_eq!(resync.command(), "resync_request");
+        assert_eq!(resync.receiver(), "runner0");
+    }
+
+    #[tokio::test]
+    async fn should_forward_drops_a_repeated_id_from_the_same_sender() {
+        let communicator = Communicator::new(test_config()).with_dedup_window(8);
+        let message = Message::request("ping", "runner0", "communicator", vec![]);
+
+        assert!(communicator.should_forward(&message).await);
+        assert!(!communicator.should_forward(&message).await);
+    }
+
+    #[tokio::test]
+    async fn should_forward_allows_distinct_ids() {
+        let communicator = Communicator::new(test_config()).with_dedup_window(8);
+        let first = Message::request("ping", "runner0", "communicator", vec![]);
+        let second = Message::request("ping", "runner0", "communicator", vec![]);
+
+        assert!(communicator.should_forward(&first).await);
+        assert!(communicator.should_forward(&second).await);
+    }
+
+    #[tokio::test]
+    async fn spawn_handler_assigns_sequential_ids_within_a_kind() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let _client0 = ClientStream::connect(addr).await.unwrap();
+        let _client1 = ClientStream::connect(addr).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let ids: Vec<HandlerId> = communicator.handlers.lock().await.keys().copied().collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&HandlerId::new(ClientKind::Runner, 0)));
+        assert!(ids.contains(&HandlerId::new(ClientKind::Runner, 1)));
+    }
+
+    #[tokio::test]
+    async fn should_forward_always_forwards_when_dedup_is_disabled() {
+        let communicator = Communicator::new(test_config());
+        let message = Message::request("ping", "runner0", "communicator", vec![]);
+
+        assert!(communicator.should_forward(&message).await);
+        assert!(communicator.should_forward(&message).await);
+    }
+
+    #[tokio::test]
+    async fn metrics_count_messages_and_bytes_sent_through_a_stub_connection() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut client, PROTOCOL_VERSION).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let mut expected_bytes = 0;
+        for i in 0..3 {
+            let message = Message::request("ping", "runner0", "communicator", vec![i.to_string()]);
+            expected_bytes += write_message_raw(&mut client, &message).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let metrics = communicator.metrics().await;
+        assert_eq!(metrics.messages_received, 3);
+        assert_eq!(metrics.bytes_received, expected_bytes as u64);
+
+        communicator.stop().await;
+        let _ = read_message_raw(&mut client).await;
+
+        let metrics = communicator.metrics().await;
+        assert_eq!(metrics.messages_sent, 1);
+        assert!(metrics.bytes_sent > 0);
+    }
+
+    #[tokio::test]
+    async fn a_full_inbound_channel_pauses_the_handler_instead_of_buffering_without_limit() {
+        let config = Arc::new(Config::new(std::env::temp_dir(), false).with_inbound_queue_capacity(1));
+        let communicator = Arc::new(Communicator::new(config));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+        let mut receiver = communicator.take_inbound_receiver().await.unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut client, PROTOCOL_VERSION).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        // Never drained: with capacity 1, the handler reads one message
+        // into the channel, reads a second and blocks sending it (the
+        // channel is full), and never gets back to the select loop to read
+        // a third off the socket at all.
+        for i in 0..3 {
+            write_message_raw(&mut client, &Message::request("ping", "runner0", "communicator", vec![i.to_string()])).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let metrics = communicator.metrics().await;
+        assert_eq!(
+            metrics.messages_received, 2,
+            "the handler should be blocked on the full inbound channel, not reading the third message"
+        );
+
+        // Draining frees capacity, letting the handler proceed.
+        receiver.recv().await.unwrap();
+        receiver.recv().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let metrics = communicator.metrics().await;
+        assert_eq!(metrics.messages_received, 3);
+    }
+
+    #[tokio::test]
+    async fn a_matching_client_version_is_accepted_and_negotiated() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        let advertise = handshake_as_client(&mut client, PROTOCOL_VERSION).await;
+        assert_eq!(advertise.command(), "handshake");
+        assert_eq!(advertise.args(), &[PROTOCOL_VERSION.to_string()]);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let id = HandlerId::new(ClientKind::Runner, 0);
+        assert_eq!(communicator.handler_version(id).await, Some(PROTOCOL_VERSION));
+    }
+
+    #[tokio::test]
+    async fn a_too_old_client_version_is_rejected_and_the_connection_closed() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        let _advertise = read_message_raw(&mut client).await;
+        write_message_raw(
+            &mut client,
+            &Message::event("handshake", "test-client", "communicator", vec!["0".to_string()]),
+        )
+        .await;
+
+        let rejection = read_message_raw(&mut client).await;
+        assert_eq!(rejection.message_type(), MessageType::Error);
+
+        let mut buf = [0u8; 1];
+        let n = client.read(&mut buf).await.unwrap();
+        assert_eq!(n, 0, "the connection should be closed after a rejected handshake");
+
+        let id = HandlerId::new(ClientKind::Runner, 0);
+        assert_eq!(communicator.handler_version(id).await, None);
+    }
+
+    #[tokio::test]
+    async fn a_compatible_but_older_client_version_is_negotiated_down() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        let addr = communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        let older_version = MIN_PROTOCOL_VERSION;
+        let mut client = ClientStream::connect(addr).await.unwrap();
+        handshake_as_client(&mut client, older_version).await;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let id = HandlerId::new(ClientKind::Runner, 0);
+        assert_eq!(communicator.handler_version(id).await, Some(older_version.min(PROTOCOL_VERSION)));
+    }
+
+    #[tokio::test]
+    async fn self_test_succeeds_while_the_listener_is_running() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        assert!(communicator.self_test(ClientKind::Runner).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn self_test_errors_when_no_listener_of_that_kind_is_bound() {
+        let communicator = Communicator::new(test_config());
+        let result = communicator.self_test(ClientKind::Runner).await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn self_test_errors_once_the_communicator_has_been_stopped() {
+        let communicator = Arc::new(Communicator::new(test_config()));
+        communicator
+            .listen("127.0.0.1:0".parse().unwrap(), ClientKind::Runner)
+            .await
+            .unwrap();
+
+        communicator.stop().await;
+
+        let result = communicator.self_test(ClientKind::Runner).await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn listen_retries_binding_at_the_configured_delay_before_giving_up() {
+        // Occupy a port with a plain std listener so every bind attempt fails.
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = occupied.local_addr().unwrap();
+
+        let config = Arc::new(
+            Config::new(std::env::temp_dir(), false)
+                .with_bind_retries(2)
+                .with_bind_retry_delay(std::time::Duration::from_secs(1)),
+        );
+        let communicator = Arc::new(Communicator::new(config));
+
+        let start = tokio::time::Instant::now();
+        let result = communicator.listen(addr, ClientKind::Runner).await;
+
+        assert!(result.is_err());
+        assert_eq!(start.elapsed(), std::time::Duration::from_secs(2));
+    }
+}
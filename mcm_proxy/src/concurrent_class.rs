@@ -0,0 +1,233 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use mcm_misc::MCManageError;
+use tokio::time::timeout;
+
+/// A lightweight snapshot of a [`ConcurrentClass`] implementor's current
+/// state, for logging or an operator-facing inspection endpoint; see
+/// [`ConcurrentClass::diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// Empty unless the implementor overrides [`ConcurrentClass::diagnostics`]
+    /// with something meaningful (e.g. [`MCServer`](crate::MCServer)'s name).
+    pub name: String,
+    /// Empty unless the implementor overrides [`ConcurrentClass::diagnostics`].
+    pub status: String,
+    /// Whether the implementor's main task/thread appears to be present and
+    /// running. The default implementation reports `!self.is_restarting()`,
+    /// since a mid-restart instance is still alive but between states.
+    pub alive: bool,
+}
+
+/// A struct that runs as a background task and is shared via `Arc`, with an
+/// optional hook to quiesce (finish in-flight work, e.g. flush logs or save
+/// state) before its stop path tears anything down.
+///
+/// `async fn` in a public trait normally can't guarantee the returned
+/// future is `Send`, but every implementor here is `Send + Sync` and only
+/// ever awaited from within this workspace, so that's not a concern.
+#[allow(async_fn_in_trait)]
+pub trait ConcurrentClass: Sized {
+    /// Runs before the main stop logic. The default implementation does
+    /// nothing.
+    async fn on_before_stop(self: &Arc<Self>) {}
+
+    /// Whether this instance is currently mid-restart, e.g.
+    /// [`MCServer::restart`](crate::MCServer::restart)'s stop/start cycle,
+    /// so callers racing a start/stop can tell a transient restart from a
+    /// real stop. The default implementation always returns `false`;
+    /// implementors with a meaningful "restarting" state override it.
+    async fn is_restarting(self: &Arc<Self>) -> bool {
+        false
+    }
+
+    /// Best-effort cleanup run by [`Self::stop_with_timeout`] when it gives
+    /// up on a stop that never finished, so `self` is left in a known state
+    /// rather than stuck mid-stop indefinitely. The default implementation
+    /// does nothing; implementors with meaningful in-progress state (e.g. a
+    /// running process handle) override it.
+    async fn reset(self: &Arc<Self>) {}
+
+    /// Races `stop` against `timeout_after`, so one implementor whose stop
+    /// logic hangs (e.g. joining a main task that never returns) can't
+    /// block whoever is waiting on it.
+    ///
+    /// On elapse, runs [`Self::reset`] and returns `Err(NotReady)` instead
+    /// of waiting on `stop` any longer; `stop`'s own future is dropped at
+    /// whatever `.await` it had reached, so work it had not finished is
+    /// abandoned (tasks it spawned are not cancelled).
+    async fn stop_with_timeout<F>(self: &Arc<Self>, timeout_after: Duration, stop: F) -> Result<(), MCManageError>
+    where
+        F: Future<Output = Result<(), MCManageError>>,
+    {
+        match timeout(timeout_after, stop).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.reset().await;
+                Err(MCManageError::NotReady(format!(
+                    "stop did not complete within {timeout_after:?}"
+                )))
+            }
+        }
+    }
+
+    /// Snapshots this instance's current state for debugging, giving a
+    /// uniform inspection point across every implementor. The default
+    /// implementation leaves [`Diagnostics::name`]/[`Diagnostics::status`]
+    /// empty and reports [`Diagnostics::alive`] as `!self.is_restarting()`;
+    /// implementors with a meaningful name/status (e.g.
+    /// [`MCServer`](crate::MCServer)) override it with their own.
+    async fn diagnostics(self: &Arc<Self>) -> Diagnostics {
+        Diagnostics {
+            name: String::new(),
+            status: String::new(),
+            alive: !self.is_restarting().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct FakeWorker {
+        quiesced: AtomicBool,
+    }
+
+    impl ConcurrentClass for FakeWorker {
+        async fn on_before_stop(self: &Arc<Self>) {
+            self.quiesced.store(true, Ordering::SeqCst);
+        }
+    }
+
+    struct NoOpWorker;
+
+    impl ConcurrentClass for NoOpWorker {}
+
+    struct NamedWorker {
+        name: String,
+        started: AtomicBool,
+    }
+
+    impl ConcurrentClass for NamedWorker {
+        async fn diagnostics(self: &Arc<Self>) -> Diagnostics {
+            Diagnostics {
+                name: self.name.clone(),
+                status: if self.started.load(Ordering::SeqCst) { "Started" } else { "Stopped" }.to_string(),
+                alive: self.started.load(Ordering::SeqCst),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn default_diagnostics_reports_alive_unless_restarting() {
+        let worker = Arc::new(NoOpWorker);
+        let diagnostics = worker.diagnostics().await;
+        assert_eq!(diagnostics.name, "");
+        assert_eq!(diagnostics.status, "");
+        assert!(diagnostics.alive);
+    }
+
+    #[tokio::test]
+    async fn diagnostics_reflects_state_before_and_after_start() {
+        let worker = Arc::new(NamedWorker {
+            name: "fake".to_string(),
+            started: AtomicBool::new(false),
+        });
+
+        let before = worker.diagnostics().await;
+        assert_eq!(before.name, "fake");
+        assert_eq!(before.status, "Stopped");
+        assert!(!before.alive);
+
+        worker.started.store(true, Ordering::SeqCst);
+
+        let after = worker.diagnostics().await;
+        assert_eq!(after.name, "fake");
+        assert_eq!(after.status, "Started");
+        assert!(after.alive);
+    }
+
+    #[tokio::test]
+    async fn on_before_stop_runs_before_the_main_task_is_joined() {
+        let worker = Arc::new(FakeWorker {
+            quiesced: AtomicBool::new(false),
+        });
+
+        let main_task = {
+            let worker = Arc::clone(&worker);
+            tokio::spawn(async move {
+                worker.on_before_stop().await;
+                // The flag must already be set by the time the "main"
+                // background task observes it and finishes.
+                worker.quiesced.load(Ordering::SeqCst)
+            })
+        };
+
+        let quiesced_before_join = main_task.await.unwrap();
+        assert!(quiesced_before_join);
+        assert!(worker.quiesced.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn default_on_before_stop_is_a_no_op() {
+        let worker = Arc::new(NoOpWorker);
+        worker.on_before_stop().await;
+    }
+
+    #[tokio::test]
+    async fn default_is_restarting_is_always_false() {
+        let worker = Arc::new(NoOpWorker);
+        assert!(!worker.is_restarting().await);
+    }
+
+    #[tokio::test]
+    async fn default_reset_is_a_no_op() {
+        let worker = Arc::new(NoOpWorker);
+        worker.reset().await;
+    }
+
+    struct StuckWorker {
+        reset_called: AtomicBool,
+    }
+
+    impl ConcurrentClass for StuckWorker {
+        async fn reset(self: &Arc<Self>) {
+            self.reset_called.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_with_timeout_resets_and_errors_when_the_stop_future_never_completes() {
+        let worker = Arc::new(StuckWorker {
+            reset_called: AtomicBool::new(false),
+        });
+
+        let result = worker
+            .stop_with_timeout(
+                Duration::from_millis(20),
+                std::future::pending::<Result<(), MCManageError>>(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert!(worker.reset_called.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn stop_with_timeout_returns_the_stop_futures_own_result_when_it_finishes_in_time() {
+        let worker = Arc::new(StuckWorker {
+            reset_called: AtomicBool::new(false),
+        });
+
+        let result = worker
+            .stop_with_timeout(Duration::from_secs(5), async { Ok(()) })
+            .await;
+
+        assert!(result.is_ok());
+        assert!(!worker.reset_called.load(Ordering::SeqCst));
+    }
+}
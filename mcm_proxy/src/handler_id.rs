@@ -0,0 +1,110 @@
+use std::fmt;
+use std::str::FromStr;
+
+use mcm_misc::MCManageError;
+
+/// Which kind of connection a [`HandlerId`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientKind {
+    /// A connected mcserver runner.
+    Runner,
+    /// A connected UI/admin client.
+    Client,
+}
+
+impl ClientKind {
+    /// Parses the single-char kind prefix used in a [`HandlerId`]'s string
+    /// form (`'r'`/`'c'`), replacing ad hoc char matching at call sites.
+    pub fn from_char(c: char) -> Result<Self, MCManageError> {
+        match c {
+            'r' => Ok(ClientKind::Runner),
+            'c' => Ok(ClientKind::Client),
+            _ => Err(MCManageError::InvalidHandlerId(c.to_string())),
+        }
+    }
+
+    /// The single-char kind prefix used in a [`HandlerId`]'s string form.
+    pub fn to_char(self) -> char {
+        match self {
+            ClientKind::Runner => 'r',
+            ClientKind::Client => 'c',
+        }
+    }
+}
+
+/// A typed handler id like `r0` (the first runner) or `c1` (the second
+/// client), replacing ad hoc `chars().next()` inspection of a raw string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId {
+    pub kind: ClientKind,
+    pub index: u32,
+}
+
+impl HandlerId {
+    pub fn new(kind: ClientKind, index: u32) -> Self {
+        Self { kind, index }
+    }
+}
+
+impl fmt::Display for HandlerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.kind.to_char(), self.index)
+    }
+}
+
+impl FromStr for HandlerId {
+    type Err = MCManageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let kind = chars
+            .next()
+            .and_then(|c| ClientKind::from_char(c).ok())
+            .ok_or_else(|| MCManageError::InvalidHandlerId(s.to_string()))?;
+        let index = chars
+            .as_str()
+            .parse()
+            .map_err(|_| MCManageError::InvalidHandlerId(s.to_string()))?;
+        Ok(Self { kind, index })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_display_and_from_str() {
+        for id in [HandlerId::new(ClientKind::Runner, 0), HandlerId::new(ClientKind::Client, 12)] {
+            let parsed: HandlerId = id.to_string().parse().unwrap();
+            assert_eq!(parsed, id);
+        }
+    }
+
+    #[test]
+    fn rejects_an_unknown_kind_prefix() {
+        assert!(matches!("x5".parse::<HandlerId>(), Err(MCManageError::InvalidHandlerId(s)) if s == "x5"));
+    }
+
+    #[test]
+    fn rejects_a_missing_index() {
+        assert!(matches!("r".parse::<HandlerId>(), Err(MCManageError::InvalidHandlerId(s)) if s == "r"));
+    }
+
+    #[test]
+    fn rejects_an_empty_string() {
+        assert!(matches!("".parse::<HandlerId>(), Err(MCManageError::InvalidHandlerId(_))));
+    }
+
+    #[test]
+    fn from_char_and_to_char_round_trip_for_every_kind() {
+        for kind in [ClientKind::Runner, ClientKind::Client] {
+            assert_eq!(ClientKind::from_char(kind.to_char()).unwrap(), kind);
+        }
+    }
+
+    #[test]
+    fn from_char_rejects_an_unknown_char() {
+        assert!(matches!(ClientKind::from_char('x'), Err(MCManageError::InvalidHandlerId(s)) if s == "x"));
+    }
+}
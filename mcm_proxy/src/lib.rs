@@ -0,0 +1,25 @@
+pub mod clock;
+pub mod command_runner;
+pub mod communicator;
+pub mod concurrent_class;
+pub mod handler_id;
+pub mod log;
+pub mod mcserver;
+pub mod mcserver_manager;
+pub mod mcserver_type;
+pub mod rcon;
+pub mod server_list;
+
+pub use clock::{Clock, MockClock, SystemClock};
+pub use command_runner::{CommandRunner, ManagedProcess, SystemCommandRunner};
+#[cfg(unix)]
+pub use command_runner::AttachedProcess;
+pub use communicator::{Communicator, CommunicatorMetrics};
+pub use concurrent_class::{ConcurrentClass, Diagnostics};
+pub use handler_id::{ClientKind, HandlerId};
+pub use log::{run_file_sink, LogLevel, LogRecord, LogSink, Logger, SinkFormat};
+pub use mcserver::{MCServer, ServerEvent, Status, StopReason};
+pub use rcon::RconConnection;
+pub use mcserver_manager::{ManagerEvent, MCServerManager, ServerListConfig};
+pub use mcserver_type::{MCServerType, MCServerTypeCache, StartedPattern};
+pub use server_list::{load_mcserver_list, load_server_list, ServerListEntry, ServerTemplate};
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+
+/// How many consecutive write failures (e.g. `ENOSPC` on a full disk)
+/// [`run_file_sink`] tolerates before giving up on writing to disk, so a
+/// stuck writer degrades to dropping records instead of spinning forever.
+const MAX_CONSECUTIVE_WRITE_FAILURES: u32 = 5;
+
+/// The severity of a [`LogRecord`].
+///
+/// Ordered by increasing severity (`Info < Warn < Error`) so a sink's
+/// [`LogSink::min_level`] can be compared against a record's level with
+/// `>=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevel::Info => write!(f, "INFO"),
+            LogLevel::Warn => write!(f, "WARN"),
+            LogLevel::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// A single application log event, as published through [`Logger::log`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub sender: String,
+    pub message: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u128,
+}
+
+/// How a [`LogSink`] renders a [`LogRecord`] before handing it off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkFormat {
+    /// `[sender] LEVEL: message`, the same shape [`Logger::log`] prints to
+    /// stderr.
+    Plain,
+    /// The record serialized as a single JSON object.
+    Json,
+}
+
+/// A destination [`Logger::log`] forwards matching records to, in addition
+/// to the always-on stderr print and broadcast publish; see
+/// [`Logger::add_sink`].
+pub struct LogSink {
+    min_level: LogLevel,
+    format: SinkFormat,
+    handler: Box<dyn Fn(String) + Send + Sync>,
+}
+
+impl LogSink {
+    /// `handler` is called with the record rendered per `format` for every
+    /// record at or above `min_level`; records below it are never even
+    /// rendered.
+    pub fn new(min_level: LogLevel, format: SinkFormat, handler: impl Fn(String) + Send + Sync + 'static) -> Self {
+        Self {
+            min_level,
+            format,
+            handler: Box::new(handler),
+        }
+    }
+
+    fn render(&self, record: &LogRecord) -> String {
+        match self.format {
+            SinkFormat::Plain => format!("[{}] {}: {}", record.sender, record.level, record.message),
+            SinkFormat::Json => serde_json::to_string(record).expect("LogRecord serialization cannot fail"),
+        }
+    }
+}
+
+/// Application-wide log sink. Every call to [`Logger::log`] prints to
+/// stderr, publishes a [`LogRecord`] to every subscriber obtained via
+/// [`Logger::log_subscribe`], and dispatches it to every [`LogSink`]
+/// registered via [`Logger::add_sink`] whose level filter it passes, so
+/// multiple consumers (a file, the console, a network forwarder) can all
+/// observe the same stream without the logger needing to know about any of
+/// them.
+pub struct Logger {
+    sender: broadcast::Sender<LogRecord>,
+    sinks: Mutex<Vec<LogSink>>,
+}
+
+impl Logger {
+    /// `capacity` is the number of past records a lagging subscriber can
+    /// fall behind by before it starts missing them; see
+    /// [`broadcast::channel`].
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            sinks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers `sink` to receive every record logged from now on that
+    /// passes its level filter.
+    pub fn add_sink(&self, sink: LogSink) {
+        self.sinks.lock().expect("log sinks mutex poisoned").push(sink);
+    }
+
+    /// Prints `message` to stderr prefixed with `sender` and `level`,
+    /// publishes the same as a [`LogRecord`] to every subscriber, and
+    /// dispatches it to every registered [`LogSink`] whose level filter it
+    /// passes. A record is still printed/dispatched even if there are no
+    /// subscribers or sinks.
+    pub fn log(&self, level: LogLevel, sender: impl Into<String>, message: impl Into<String>) {
+        let record = LogRecord {
+            level,
+            sender: sender.into(),
+            message: message.into(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system clock is before the Unix epoch")
+                .as_millis(),
+        };
+        eprintln!("[{}] {}: {}", record.sender, record.level, record.message);
+
+        for sink in self.sinks.lock().expect("log sinks mutex poisoned").iter() {
+            if record.level >= sink.min_level {
+                (sink.handler)(sink.render(&record));
+            }
+        }
+
+        // No subscribers is a normal, expected state, not an error.
+        let _ = self.sender.send(record);
+    }
+
+    /// Subscribes to every [`LogRecord`] published from now on.
+    pub fn log_subscribe(&self) -> broadcast::Receiver<LogRecord> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+/// Writes every [`LogRecord`] received from `subscriber` to `writer` as a
+/// line, until the channel closes. A failing write (e.g. the disk backing
+/// `writer` filling up) is retried on the next record rather than
+/// immediately, but after [`MAX_CONSECUTIVE_WRITE_FAILURES`] consecutive
+/// failures the sink gives up on disk entirely: it logs the condition once
+/// to stderr and drops every subsequent record instead of retrying forever,
+/// so a full disk can't spin this task or back up the broadcast channel.
+pub async fn run_file_sink<W>(mut subscriber: broadcast::Receiver<LogRecord>, mut writer: W)
+where
+    W: AsyncWriteExt + Unpin,
+{
+    let mut consecutive_failures = 0u32;
+
+    loop {
+        let record = match subscriber.recv().await {
+            Ok(record) => record,
+            Err(broadcast::error::RecvError::Closed) => break,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+        };
+
+        if consecutive_failures >= MAX_CONSECUTIVE_WRITE_FAILURES {
+            continue;
+        }
+
+        let line = format!("[{}] {}: {}\n", record.sender, record.level, record.message);
+        match writer.write_all(line.as_bytes()).await {
+            Ok(()) => consecutive_failures = 0,
+            Err(err) => {
+                consecutive_failures += 1;
+                if consecutive_failures == MAX_CONSECUTIVE_WRITE_FAILURES {
+                    eprintln!(
+                        "log file sink: giving up on disk logging after {consecutive_failures} \
+                         consecutive write failures, dropping further records: {err}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use tokio::io::AsyncWrite;
+
+    /// An `AsyncWrite` that always fails, simulating a disk that's full
+    /// (`ENOSPC`). Counts how many times a write was attempted.
+    struct FailingWriter {
+        attempts: Arc<AtomicU32>,
+    }
+
+    impl AsyncWrite for FailingWriter {
+        fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, _buf: &[u8]) -> Poll<io::Result<usize>> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            Poll::Ready(Err(io::Error::other("no space left on device")))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn every_subscriber_receives_every_logged_record() {
+        let logger = Logger::default();
+        let mut first = logger.log_subscribe();
+        let mut second = logger.log_subscribe();
+
+        logger.log(LogLevel::Info, "mcserver_manager", "server started");
+        logger.log(LogLevel::Error, "communicator", "handler disconnected");
+
+        for subscriber in [&mut first, &mut second] {
+            let record = subscriber.recv().await.unwrap();
+            assert_eq!(record.sender, "mcserver_manager");
+            assert_eq!(record.message, "server started");
+            let record = subscriber.recv().await.unwrap();
+            assert_eq!(record.sender, "communicator");
+            assert_eq!(record.message, "handler disconnected");
+        }
+    }
+
+    #[tokio::test]
+    async fn a_subscription_only_sees_records_published_after_it_was_made() {
+        let logger = Logger::default();
+        logger.log(LogLevel::Info, "mcserver_manager", "before subscribing");
+
+        let mut subscriber = logger.log_subscribe();
+        logger.log(LogLevel::Info, "mcserver_manager", "after subscribing");
+
+        let record = subscriber.recv().await.unwrap();
+        assert_eq!(record.message, "after subscribing");
+    }
+
+    #[test]
+    fn each_sink_only_receives_records_meeting_its_own_level_threshold() {
+        let logger = Logger::default();
+        let file_lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let console_lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let file_sink_lines = Arc::clone(&file_lines);
+        logger.add_sink(LogSink::new(LogLevel::Info, SinkFormat::Plain, move |line| {
+            file_sink_lines.lock().unwrap().push(line);
+        }));
+        let console_sink_lines = Arc::clone(&console_lines);
+        logger.add_sink(LogSink::new(LogLevel::Warn, SinkFormat::Plain, move |line| {
+            console_sink_lines.lock().unwrap().push(line);
+        }));
+
+        logger.log(LogLevel::Info, "mcserver_manager", "server started");
+        logger.log(LogLevel::Warn, "mcserver_manager", "server slow to respond");
+        logger.log(LogLevel::Error, "mcserver_manager", "server crashed");
+
+        assert_eq!(file_lines.lock().unwrap().len(), 3);
+        let console_lines = console_lines.lock().unwrap();
+        assert_eq!(console_lines.len(), 2);
+        assert!(console_lines[0].contains("slow to respond"));
+        assert!(console_lines[1].contains("crashed"));
+    }
+
+    #[test]
+    fn a_json_sink_renders_the_record_as_a_json_object() {
+        let logger = Logger::default();
+        let lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_lines = Arc::clone(&lines);
+        logger.add_sink(LogSink::new(LogLevel::Info, SinkFormat::Json, move |line| {
+            sink_lines.lock().unwrap().push(line);
+        }));
+
+        logger.log(LogLevel::Info, "mcserver_manager", "server started");
+
+        let lines = lines.lock().unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(parsed["sender"], "mcserver_manager");
+        assert_eq!(parsed["message"], "server started");
+    }
+
+    #[tokio::test]
+    async fn run_file_sink_gives_up_after_the_bound_instead_of_retrying_forever() {
+        let logger = Logger::default();
+        let subscriber = logger.log_subscribe();
+        let attempts = Arc::new(AtomicU32::new(0));
+        let writer = FailingWriter { attempts: Arc::clone(&attempts) };
+        let sink = tokio::spawn(run_file_sink(subscriber, writer));
+
+        for i in 0..(MAX_CONSECUTIVE_WRITE_FAILURES * 3) {
+            logger.log(LogLevel::Info, "mcserver_manager", format!("record {i}"));
+        }
+        // Let the sink task drain the channel before checking how many
+        // writes it attempted.
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+
+        sink.abort();
+        assert_eq!(attempts.load(Ordering::SeqCst), MAX_CONSECUTIVE_WRITE_FAILURES);
+    }
+}
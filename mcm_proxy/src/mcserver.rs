@@ -0,0 +1,3614 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use arc_swap::ArcSwap;
+use mcm_misc::{Config, MCManageError};
+use regex::Regex;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::{broadcast, Mutex};
+use tokio::time::{sleep, timeout};
+
+use crate::command_runner::{CommandRunner, ManagedProcess, SystemCommandRunner};
+use crate::concurrent_class::{ConcurrentClass, Diagnostics};
+use crate::mcserver_type::StartedPattern;
+use crate::rcon::RconConnection;
+
+/// Vanilla gamerule names, checked (permissively — see
+/// [`MCServer::set_gamerule`]) against a rule passed to
+/// [`MCServer::set_gamerule`] or [`MCServer::get_gamerule`] to catch a
+/// likely typo without rejecting a modded server's own gamerules.
+const KNOWN_GAMERULES: &[&str] = &[
+    "announceAdvancements",
+    "commandBlockOutput",
+    "disableElytraMovementCheck",
+    "disableRaids",
+    "doDaylightCycle",
+    "doEntityDrops",
+    "doFireTick",
+    "doImmediateRespawn",
+    "doInsomnia",
+    "doLimitedCrafting",
+    "doMobLoot",
+    "doMobSpawning",
+    "doPatrolSpawning",
+    "doTileDrops",
+    "doTraderSpawning",
+    "doVinesSpread",
+    "doWeatherCycle",
+    "drowningDamage",
+    "fallDamage",
+    "fireDamage",
+    "forgiveDeadPlayers",
+    "freezeDamage",
+    "keepInventory",
+    "lavaSourceConversion",
+    "logAdminCommands",
+    "maxCommandChainLength",
+    "maxEntityCramming",
+    "mobGriefing",
+    "naturalRegeneration",
+    "playersSleepingPercentage",
+    "randomTickSpeed",
+    "reducedDebugInfo",
+    "sendCommandFeedback",
+    "showDeathMessages",
+    "spawnRadius",
+    "spectatorsGenerateChunks",
+    "tntExplodes",
+    "universalAnger",
+    "waterSourceConversion",
+];
+
+/// The lifecycle state of a managed [`MCServer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Stopped,
+    Starting,
+    Started,
+    Restarting,
+    Crashed,
+    /// Hit [`MCServer::with_max_lifetime_restarts`]'s cap and was taken out
+    /// of auto-recovery; see [`MCServer::reset_failure`].
+    Failed,
+}
+
+/// Why [`MCServer::stop`] last tore the process down, recorded so
+/// dashboards and debugging can tell the difference; see
+/// [`MCServer::last_stop_reason`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// An operator explicitly requested the stop.
+    Operator,
+    /// The server was stopped after sitting idle with no players; see
+    /// [`crate::mcserver_manager::MCServerManager::warn_and_await_idle_shutdown`].
+    IdleShutdown,
+    /// The server was running and crashed.
+    Crash,
+    /// Stopped as the first half of [`MCServer::restart`].
+    Restart,
+    /// Never reached [`Status::Started`] within `max_tries` attempts (or
+    /// failed a pre-start check), and was marked [`Status::Crashed`]
+    /// without a process ever having run long enough to stop.
+    FailedStart,
+}
+
+/// Parses a JVM `-Xmx` value (e.g. `2G`, `512m`, or a bare byte count) into
+/// megabytes; see [`MCServer::configured_memory_mb`]. Returns `None` if
+/// `value` is empty or doesn't parse.
+fn parse_xmx_mb(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let last = value.chars().last()?;
+    if last.is_ascii_alphabetic() {
+        let number: u64 = value[..value.len() - last.len_utf8()].parse().ok()?;
+        match last.to_ascii_lowercase() {
+            'g' => Some(number * 1024),
+            'm' => Some(number),
+            'k' => Some(number / 1024),
+            _ => None,
+        }
+    } else {
+        let bytes: u64 = value.parse().ok()?;
+        Some(bytes / (1024 * 1024))
+    }
+}
+
+/// A lifecycle transition broadcast to anyone subscribed via
+/// [`MCServer::subscribe_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    Started,
+    Stopped(StopReason),
+    Crashed,
+    Restarting,
+    /// A stdout line matched [`MCServer::with_crash_report_pattern`],
+    /// carrying the captured crash report path; see
+    /// [`MCServer::last_crash_report`].
+    CrashReportDetected(PathBuf),
+    /// [`MCServer::with_max_lifetime_restarts`]'s cap was reached; see
+    /// [`Status::Failed`].
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Stopped => write!(f, "Stopped"),
+            Status::Starting => write!(f, "Starting"),
+            Status::Started => write!(f, "Started"),
+            Status::Restarting => write!(f, "Restarting"),
+            Status::Crashed => write!(f, "Crashed"),
+            Status::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// A single Minecraft server managed by this proxy.
+pub struct MCServer {
+    name: String,
+    path: PathBuf,
+    config: Arc<Config>,
+    /// The configured `MCServerType` name, kept around purely so config
+    /// round-tripping (see [`crate::mcserver_manager::MCServerManager::export_config`])
+    /// can reconstruct `server_list.json` without a second source of truth.
+    server_type: Option<String>,
+    command: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    /// Patterns, any ONE of which marks a stdout line as indicating the
+    /// server finished starting.
+    started_patterns: Vec<StartedPattern>,
+    startup_deadline: Duration,
+    max_tries: u32,
+    /// How long to wait after the started line matches before actually
+    /// transitioning to [`Status::Started`]; see
+    /// [`Self::with_startup_grace`]. Status stays [`Status::Starting`] for
+    /// the whole delay. Zero (the default) transitions immediately.
+    startup_grace: Duration,
+    /// Console commands sent, in order, the moment the server reaches
+    /// [`Status::Started`]; see [`Self::with_on_start_commands`].
+    on_start_commands: Vec<String>,
+    /// The command sent to the server to flush and save the world, and the
+    /// phrase (and timeout) that confirms it completed.
+    save_command: String,
+    save_confirmation_phrase: String,
+    save_timeout: Duration,
+    /// The command sent to reload the server's configuration in place, and
+    /// the phrase (and timeout) that confirms it completed; see
+    /// [`Self::reload`] and [`Self::with_reload_command`].
+    reload_command: String,
+    reload_confirmation_phrase: String,
+    reload_timeout: Duration,
+    /// Prefix logged ahead of input sent via [`Self::send_input`], so
+    /// operators can tell manual input apart from server output in the
+    /// log. Not logged at all for [`Self::send_input_silent`].
+    input_log_prefix: String,
+    /// The maximum byte length of a single stdout line kept/broadcast
+    /// before it's truncated; see [`Self::with_max_line_length`].
+    max_line_length: usize,
+    /// Checked to exist and be a readable regular file before every
+    /// [`Self::start`] spawns the process; see [`Self::with_jar_path`].
+    jar_path: Option<PathBuf>,
+    /// Run (and waited on) before the process is spawned on every
+    /// [`Self::start`]; see [`Self::with_pre_start_command`].
+    pre_start_command: Option<(String, Vec<String>)>,
+    /// Run (and waited on) after the process has stopped on every
+    /// [`Self::stop`]; see [`Self::with_post_stop_command`].
+    post_stop_command: Option<(String, Vec<String>)>,
+    /// The command sent to gracefully stop the server; see
+    /// [`Self::with_stop_escalation`].
+    stop_command: String,
+    /// How long [`Self::stop`] waits for the process to exit after
+    /// `stop_command` before escalating to SIGTERM; see
+    /// [`Self::with_stop_escalation`].
+    stop_graceful_timeout: Duration,
+    /// How long [`Self::stop`] waits for the process to exit after SIGTERM
+    /// (unix only) before escalating to SIGKILL; see
+    /// [`Self::with_stop_escalation`].
+    stop_term_timeout: Duration,
+    status: Mutex<Status>,
+    /// Why the server last stopped (or failed to start); see
+    /// [`Self::last_stop_reason`]. `None` until the first stop/failed
+    /// start.
+    stop_reason: Mutex<Option<StopReason>>,
+    /// The running process, abstracted behind [`ManagedProcess`] so tests
+    /// can inject a canned fake instead of a real OS process; see
+    /// [`Self::with_command_runner`]. Consumed both by the startup watchdog
+    /// in [`Self::start`] and by anything that needs to watch for a
+    /// specific line afterwards, such as [`Self::save_world`].
+    process: Mutex<Option<Box<dyn ManagedProcess>>>,
+    /// Spawns [`Self::process`] on every [`Self::start`] attempt; see
+    /// [`Self::with_command_runner`].
+    runner: Box<dyn CommandRunner>,
+    /// The currently online players, stored behind an `ArcSwap` so reads
+    /// (`players()`) never block on the writes `check_player_activity`
+    /// makes under join/leave churn.
+    players: ArcSwap<Vec<String>>,
+    /// Whether [`Self::handle_player_leave`] restarts the server when told a
+    /// player left who was never tracked as having joined, rather than
+    /// logging and ignoring it; see [`Self::with_strict_unknown_leave_handling`].
+    strict_unknown_leave: bool,
+    /// The directory every stdout line is mirrored into as `{name}.txt`;
+    /// see [`Self::with_log_path`]. `None` disables file logging entirely.
+    log_path: Option<PathBuf>,
+    /// Whether [`Self::start`] archives the previous `{name}.txt` to
+    /// `{name}.{timestamp}.txt` and begins a fresh file, rather than
+    /// appending to the same file across restarts; see
+    /// [`Self::with_log_per_session`].
+    log_per_session: bool,
+    /// The currently open log file, opened (and, if `log_per_session`,
+    /// rotated) by [`Self::open_log_file`] on every [`Self::start`].
+    log_file: Mutex<Option<tokio::fs::File>>,
+    /// Caps how many stdout lines per second [`Self::next_stdout_line`]
+    /// logs/exposes to callers; see [`Self::with_max_line_rate`]. `None`
+    /// (the default) leaves lines unthrottled.
+    max_line_rate: Option<u32>,
+    /// Tracks [`Self::max_line_rate`]'s current one-second window.
+    line_rate_window: Mutex<LineRateWindow>,
+    /// How long [`Self::get_gamerule`] waits for the server to echo a
+    /// queried gamerule's value; see [`Self::with_gamerule_timeout`].
+    gamerule_timeout: Duration,
+    /// Whether the child process is killed when [`Self::process`] is
+    /// dropped (e.g. the last `Arc<MCServer>` going away) instead of being
+    /// left to run detached; see [`Self::with_kill_on_drop`]. Defaults to
+    /// `true` so a removed/forgotten server can't orphan a Java process.
+    kill_on_drop: bool,
+    /// `server.properties` keys forced to a fixed value on every
+    /// [`Self::start`], regardless of what's on disk; see
+    /// [`Self::with_properties_overrides`].
+    properties_overrides: HashMap<String, String>,
+    /// Whether [`crate::MCServerManager::start_all`]'s auto-start loop may
+    /// start this server; see [`Self::with_enabled`]. Defaults to `true`.
+    /// A disabled server is still constructed and managed normally — it
+    /// can still be started explicitly, e.g. via
+    /// [`crate::MCServerManager::restart_server`] or [`Self::start`] itself.
+    enabled: bool,
+    /// The `host:port` address and password [`Self::rcon_command`] sends
+    /// commands to; see [`Self::with_rcon`]. `None` (the default) makes
+    /// [`Self::rcon_command`] return [`MCManageError::NotReady`].
+    rcon: Option<(String, String)>,
+    /// How long [`Self::rcon_command`] waits for a reply before giving up;
+    /// see [`Self::with_rcon_command_timeout`]. Defaults to
+    /// [`mcm_misc::Config::rcon_command_timeout`].
+    rcon_command_timeout: Duration,
+    /// The RCON connection reused across [`Self::rcon_command`] calls,
+    /// once one has been authenticated; dropped on any error (including a
+    /// timeout) so the next call starts from a fresh connection instead of
+    /// reusing one left in an unknown state.
+    rcon_connection: Mutex<Option<RconConnection>>,
+    /// Broadcasts every [`ServerEvent`] to anyone subscribed via
+    /// [`Self::subscribe_events`]. Created once in [`Self::new`] and never
+    /// replaced afterwards — in particular, neither [`Self::restart`] nor
+    /// [`ConcurrentClass::reset`] touch it — so a subscriber's
+    /// [`broadcast::Receiver`] keeps receiving events across a restart
+    /// instead of being dropped and needing to resubscribe.
+    events: broadcast::Sender<ServerEvent>,
+    /// A regex (with one capture group for the path) checked against every
+    /// stdout line; see [`Self::with_crash_report_pattern`]. `None` (the
+    /// default) disables crash report detection.
+    crash_report_pattern: Option<Regex>,
+    /// The path of the most recently detected crash report; see
+    /// [`Self::last_crash_report`].
+    last_crash_report: Mutex<Option<PathBuf>>,
+    /// Caps total restarts (across this server's whole lifetime, not just
+    /// within a window) before [`Self::restart`] marks it [`Status::Failed`]
+    /// instead of actually restarting; see
+    /// [`Self::with_max_lifetime_restarts`]. `None` (the default) leaves
+    /// restarts uncapped.
+    max_lifetime_restarts: Option<u64>,
+    /// How many times [`Self::restart`] has actually restarted the server
+    /// so far; see [`Self::max_lifetime_restarts`] and
+    /// [`Self::reset_failure`].
+    restart_count: Mutex<u64>,
+    /// Broadcasts every admitted stdout line (the same ones
+    /// [`Self::write_log_line`] mirrors to the log file) to anyone
+    /// subscribed via [`Self::subscribe_output`], for a live log viewer
+    /// that wants to tail output without reading the file. Like
+    /// [`Self::events`], created once in [`Self::new`] and never replaced,
+    /// so a subscriber keeps receiving lines across a restart. Bounded, so
+    /// a subscriber that falls behind sees
+    /// [`tokio::sync::broadcast::error::RecvError::Lagged`] on its next
+    /// `recv` rather than this Mutex-free broadcast buffering lines
+    /// without limit.
+    output: broadcast::Sender<String>,
+    /// Overrides `Config::agree_to_eula` for this server only; see
+    /// [`Self::with_agree_to_eula`]. `None` (the default) defers to the
+    /// global config.
+    agree_to_eula_override: Option<bool>,
+}
+
+/// The bookkeeping behind [`MCServer::with_max_line_rate`]: how many lines
+/// have been admitted in the current one-second window, and how many have
+/// been dropped since the last window reset.
+#[derive(Debug, Default)]
+struct LineRateWindow {
+    started_at: Option<Instant>,
+    admitted: u32,
+    dropped: u32,
+}
+
+/// What [`MCServer::admit_line`] decided to do with the next stdout line.
+enum LineAdmission {
+    /// Within the current rate budget: log/expose the line as usual.
+    Admit,
+    /// Over the current rate budget: count it and surface nothing.
+    Drop,
+    /// The first admitted line of a fresh window that followed one or more
+    /// drops: log a `"dropped N line(s)"` summary before this line.
+    AdmitWithSummary(u32),
+}
+
+impl MCServer {
+    pub fn new(name: impl Into<String>, config: Arc<Config>) -> Self {
+        let name = name.into();
+        let path = config.server_path.join(&name);
+        let rcon_command_timeout = config.rcon_command_timeout;
+        Self {
+            name,
+            path,
+            config,
+            server_type: None,
+            command: String::new(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            started_patterns: Vec::new(),
+            startup_deadline: Duration::from_secs(300),
+            max_tries: 3,
+            startup_grace: Duration::ZERO,
+            on_start_commands: Vec::new(),
+            save_command: "save-all flush".to_string(),
+            save_confirmation_phrase: "Saved the game".to_string(),
+            save_timeout: Duration::from_secs(10),
+            reload_command: "reload confirm".to_string(),
+            reload_confirmation_phrase: "Reload complete".to_string(),
+            reload_timeout: Duration::from_secs(10),
+            input_log_prefix: ">> ".to_string(),
+            max_line_length: 8192,
+            jar_path: None,
+            pre_start_command: None,
+            post_stop_command: None,
+            stop_command: "stop".to_string(),
+            stop_graceful_timeout: Duration::from_secs(30),
+            stop_term_timeout: Duration::from_secs(10),
+            status: Mutex::new(Status::Stopped),
+            stop_reason: Mutex::new(None),
+            process: Mutex::new(None),
+            runner: Box::new(SystemCommandRunner),
+            players: ArcSwap::from_pointee(Vec::new()),
+            strict_unknown_leave: false,
+            log_path: None,
+            log_per_session: false,
+            log_file: Mutex::new(None),
+            max_line_rate: None,
+            line_rate_window: Mutex::new(LineRateWindow::default()),
+            gamerule_timeout: Duration::from_secs(10),
+            kill_on_drop: true,
+            properties_overrides: HashMap::new(),
+            enabled: true,
+            rcon: None,
+            rcon_command_timeout,
+            rcon_connection: Mutex::new(None),
+            events: broadcast::channel(32).0,
+            crash_report_pattern: None,
+            last_crash_report: Mutex::new(None),
+            max_lifetime_restarts: None,
+            restart_count: Mutex::new(0),
+            output: broadcast::channel(256).0,
+            agree_to_eula_override: None,
+        }
+    }
+
+    /// Records which configured `MCServerType` this server uses, purely so
+    /// it can be reported back by
+    /// [`crate::mcserver_manager::MCServerManager::export_config`].
+    pub fn with_server_type(mut self, server_type: impl Into<String>) -> Self {
+        self.server_type = Some(server_type.into());
+        self
+    }
+
+    /// Sets the command used to launch this server's process (e.g. `java`
+    /// with `-jar server.jar`).
+    pub fn with_command(mut self, command: impl Into<String>, args: Vec<String>) -> Self {
+        self.command = command.into();
+        self.args = args;
+        self
+    }
+
+    /// Overrides how [`Self::start`] spawns the server's process (default
+    /// [`SystemCommandRunner`], a real OS process). Tests inject a fake
+    /// [`CommandRunner`] that emits scripted startup/join/leave lines
+    /// without spawning anything, to exercise this type's parsing and state
+    /// logic offline.
+    pub fn with_command_runner(mut self, runner: impl CommandRunner + 'static) -> Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    /// Sets environment variables (e.g. `JAVA_TOOL_OPTIONS`) passed to the
+    /// launched process in addition to the inherited environment.
+    ///
+    /// Keys that are empty or contain `=` or a NUL byte aren't valid
+    /// environment variable names on any platform, so they're dropped with
+    /// a warning instead of being handed to [`Command::envs`], which would
+    /// otherwise panic.
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env
+            .into_iter()
+            .filter(|(key, _)| {
+                let valid = !key.is_empty() && !key.contains('=') && !key.contains('\0');
+                if !valid {
+                    eprintln!("[{}] ignoring invalid environment variable key '{key}'", self.name);
+                }
+                valid
+            })
+            .collect();
+        self
+    }
+
+    /// Sets the literal phrases, any ONE of which marks a stdout line as
+    /// indicating the server finished starting.
+    pub fn with_started_phrases(self, started_phrases: Vec<String>) -> Self {
+        self.with_started_patterns(started_phrases.into_iter().map(StartedPattern::Phrase).collect())
+    }
+
+    /// Sets the started-detection patterns (phrases and/or regexes), any
+    /// ONE of which marks a stdout line as indicating the server finished
+    /// starting.
+    pub fn with_started_patterns(mut self, started_patterns: Vec<StartedPattern>) -> Self {
+        self.started_patterns = started_patterns;
+        self
+    }
+
+    /// Sets how long a start attempt may take before it's considered a
+    /// startup deadlock, and how many attempts to make before giving up.
+    pub fn with_startup_watchdog(mut self, startup_deadline: Duration, max_tries: u32) -> Self {
+        self.startup_deadline = startup_deadline;
+        self.max_tries = max_tries;
+        self
+    }
+
+    /// Sets how long to wait, after the started line matches, before
+    /// actually transitioning to [`Status::Started`] (status stays
+    /// [`Status::Starting`] for the delay). Some servers print their
+    /// started line before plugins have finished loading, so a caller
+    /// that needs the server to be truly ready (not just about to be) can
+    /// pad this per [`crate::MCServerType`].
+    pub fn with_startup_grace(mut self, startup_grace: Duration) -> Self {
+        self.startup_grace = startup_grace;
+        self
+    }
+
+    /// Sets console commands (e.g. `gamerule doDaylightCycle false`) sent in
+    /// order, one per line, the moment the server reaches [`Status::Started`]
+    /// on every [`Self::start`] — an "autoexec" for setup that has to run
+    /// after the server is up rather than at spawn time.
+    pub fn with_on_start_commands(mut self, on_start_commands: Vec<String>) -> Self {
+        self.on_start_commands = on_start_commands;
+        self
+    }
+
+    /// Overrides the command, confirmation phrase and timeout used by
+    /// [`Self::save_world`], for server types that phrase these differently
+    /// (e.g. Bedrock's `save hold`/`save query`).
+    pub fn with_save_command(
+        mut self,
+        save_command: impl Into<String>,
+        save_confirmation_phrase: impl Into<String>,
+        save_timeout: Duration,
+    ) -> Self {
+        self.save_command = save_command.into();
+        self.save_confirmation_phrase = save_confirmation_phrase.into();
+        self.save_timeout = save_timeout;
+        self
+    }
+
+    /// Overrides the command, confirmation phrase and timeout used by
+    /// [`Self::reload`], for server types that phrase these differently.
+    pub fn with_reload_command(
+        mut self,
+        reload_command: impl Into<String>,
+        reload_confirmation_phrase: impl Into<String>,
+        reload_timeout: Duration,
+    ) -> Self {
+        self.reload_command = reload_command.into();
+        self.reload_confirmation_phrase = reload_confirmation_phrase.into();
+        self.reload_timeout = reload_timeout;
+        self
+    }
+
+    /// Overrides the prefix logged ahead of input sent via
+    /// [`Self::send_input`] (default `">> "`).
+    pub fn with_input_log_prefix(mut self, input_log_prefix: impl Into<String>) -> Self {
+        self.input_log_prefix = input_log_prefix.into();
+        self
+    }
+
+    /// Overrides the maximum byte length of a single stdout line (default
+    /// 8192) before it's truncated at a char boundary with an elision
+    /// marker, guarding against a malformed line (e.g. a giant stack
+    /// trace) spiking memory when stored/broadcast.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    /// Caps how many stdout lines per second are logged/exposed to callers,
+    /// so a server (or a malicious process impersonating one) emitting
+    /// lines far faster than that can't saturate log I/O or whoever's
+    /// watching for specific lines (e.g. [`Self::save_world`]). Lines
+    /// beyond the cap within a given second are counted and dropped
+    /// instead; once the window resets, a single `"dropped N line(s)"`
+    /// summary is logged in their place. Unset (the default) leaves lines
+    /// unthrottled.
+    pub fn with_max_line_rate(mut self, max_line_rate: u32) -> Self {
+        self.max_line_rate = Some(max_line_rate);
+        self
+    }
+
+    /// Overrides how long [`Self::get_gamerule`] waits for the server to
+    /// echo a queried gamerule's value (default 10 seconds).
+    pub fn with_gamerule_timeout(mut self, gamerule_timeout: Duration) -> Self {
+        self.gamerule_timeout = gamerule_timeout;
+        self
+    }
+
+    /// Controls whether the child process is killed when it's dropped
+    /// (default `true`), e.g. because this `MCServer`'s last `Arc` went
+    /// away without a graceful [`Self::stop`]. Set to `false` to leave the
+    /// process running detached instead — useful for operators who expect
+    /// a server to survive the manager restarting.
+    pub fn with_kill_on_drop(mut self, kill_on_drop: bool) -> Self {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Forces the given `server.properties` keys to these values on every
+    /// [`Self::start`], so operators can enforce settings like
+    /// `enable-rcon=true` or a fixed port regardless of what's on disk.
+    /// Applied by [`Self::apply_properties_overrides`]; unrelated keys
+    /// already in the file are left untouched.
+    pub fn with_properties_overrides(mut self, properties_overrides: HashMap<String, String>) -> Self {
+        self.properties_overrides = properties_overrides;
+        self
+    }
+
+    /// Controls whether [`crate::MCServerManager::start_all`]'s auto-start
+    /// loop may start this server (default `true`). A disabled server is
+    /// still constructed and fully managed — it can always be started
+    /// explicitly regardless of this setting.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Overrides `Config::agree_to_eula` for this server only, taking
+    /// precedence over it in [`Self::agree_to_eula`] — e.g. an operator
+    /// auto-accepting for their own servers while leaving the global
+    /// default requiring manual acceptance for everyone else's.
+    pub fn with_agree_to_eula(mut self, agree_to_eula: bool) -> Self {
+        self.agree_to_eula_override = Some(agree_to_eula);
+        self
+    }
+
+    /// Configures the `host:port` address and password [`Self::rcon_command`]
+    /// sends commands to. Unset (the default) makes [`Self::rcon_command`]
+    /// return [`MCManageError::NotReady`].
+    pub fn with_rcon(mut self, address: impl Into<String>, password: impl Into<String>) -> Self {
+        self.rcon = Some((address.into(), password.into()));
+        self
+    }
+
+    /// Overrides how long [`Self::rcon_command`] waits for a reply before
+    /// giving up. Defaults to [`mcm_misc::Config::rcon_command_timeout`].
+    pub fn with_rcon_command_timeout(mut self, rcon_command_timeout: Duration) -> Self {
+        self.rcon_command_timeout = rcon_command_timeout;
+        self
+    }
+
+    /// Controls how [`Self::handle_player_leave`] reacts to a leave for a
+    /// player who was never tracked as having joined — the legitimate case
+    /// where the manager (re)started after players had already connected.
+    /// Tolerant (the default, `false`) logs the anomaly and ignores it;
+    /// strict (`true`) treats it as a critical inconsistency and restarts
+    /// the server.
+    pub fn with_strict_unknown_leave_handling(mut self, strict: bool) -> Self {
+        self.strict_unknown_leave = strict;
+        self
+    }
+
+    /// Sets the directory every stdout line is mirrored into as
+    /// `{name}.txt`, in addition to being available via
+    /// [`Self::next_stdout_line`]'s callers. Unset (the default) disables
+    /// file logging entirely.
+    pub fn with_log_path(mut self, log_path: impl Into<PathBuf>) -> Self {
+        self.log_path = Some(log_path.into());
+        self
+    }
+
+    /// Controls how [`Self::start`] handles an existing `{name}.txt` in
+    /// [`Self::with_log_path`]'s directory. Per-session (`true`) archives it
+    /// to `{name}.{timestamp}.txt` and begins a fresh file; the default
+    /// (`false`) keeps appending to the same file across restarts.
+    pub fn with_log_per_session(mut self, log_per_session: bool) -> Self {
+        self.log_per_session = log_per_session;
+        self
+    }
+
+    /// Sets the server jar's path, checked to exist and be a readable
+    /// regular file before every [`Self::start`] spawns the process. Catches
+    /// a missing or misconfigured jar (e.g. a directory left behind by a
+    /// failed download) with a specific error instead of an opaque spawn
+    /// failure.
+    pub fn with_jar_path(mut self, jar_path: impl Into<PathBuf>) -> Self {
+        self.jar_path = Some(jar_path.into());
+        self
+    }
+
+    /// Sets a command run (and waited on) in this server's directory before
+    /// the process is spawned on every [`Self::start`] (e.g. syncing a
+    /// world from git). A non-zero exit aborts the start with an error
+    /// before Java is ever launched.
+    pub fn with_pre_start_command(mut self, command: impl Into<String>, args: Vec<String>) -> Self {
+        self.pre_start_command = Some((command.into(), args));
+        self
+    }
+
+    /// Sets a command run (and waited on) in this server's directory after
+    /// the process has stopped on every [`Self::stop`]. A failing hook is
+    /// logged rather than failing the stop, since the server is already
+    /// down by the time it runs.
+    pub fn with_post_stop_command(mut self, command: impl Into<String>, args: Vec<String>) -> Self {
+        self.post_stop_command = Some((command.into(), args));
+        self
+    }
+
+    /// Overrides the escalation ladder [`Self::stop`] climbs to stop the
+    /// process: the command sent first (default `"stop"`), how long it
+    /// waits for the process to exit before escalating to SIGTERM (default
+    /// 30s, unix only), and how long it then waits before escalating again
+    /// to SIGKILL (default 10s).
+    pub fn with_stop_escalation(
+        mut self,
+        stop_command: impl Into<String>,
+        graceful_timeout: Duration,
+        term_timeout: Duration,
+    ) -> Self {
+        self.stop_command = stop_command.into();
+        self.stop_graceful_timeout = graceful_timeout;
+        self.stop_term_timeout = term_timeout;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn server_type(&self) -> Option<&str> {
+        self.server_type.as_deref()
+    }
+
+    /// Whether [`crate::MCServerManager::start_all`]'s auto-start loop may
+    /// start this server; see [`Self::with_enabled`].
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// This server's [`Self::with_agree_to_eula`] override, if any; see
+    /// [`Self::agree_to_eula`].
+    pub fn agree_to_eula_override(&self) -> Option<bool> {
+        self.agree_to_eula_override
+    }
+
+    /// The arguments this server's process is launched with; see
+    /// [`Self::with_command`].
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The memory committed via a `-Xmx` JVM flag in [`Self::args`], in
+    /// megabytes, or `None` if no such flag is present or it doesn't parse.
+    /// Used by [`crate::MCServerManager::with_max_total_memory_mb`]'s guard
+    /// against overcommitting the host's memory across every managed
+    /// server.
+    pub fn configured_memory_mb(&self) -> Option<u64> {
+        let value = self.args.iter().find_map(|arg| arg.strip_prefix("-Xmx"))?;
+        parse_xmx_mb(value)
+    }
+
+    pub async fn status(&self) -> Status {
+        *self.status.lock().await
+    }
+
+    /// Why this server last stopped (or failed to start); see
+    /// [`StopReason`]. `None` if it has never stopped or failed to start.
+    pub async fn last_stop_reason(&self) -> Option<StopReason> {
+        *self.stop_reason.lock().await
+    }
+
+    /// Subscribes to this server's [`ServerEvent`]s, e.g. for a dashboard
+    /// that wants to react to a started/crashed/restarting transition
+    /// without polling [`Self::status`]. The returned receiver keeps
+    /// working across any number of [`Self::restart`] calls — resubscribing
+    /// afterwards is never required.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribes to every admitted stdout line, e.g. for a web log viewer
+    /// that wants to tail output live instead of reading the log file. The
+    /// returned receiver keeps working across any number of
+    /// [`Self::restart`] calls — resubscribing afterwards is never
+    /// required. A subscriber that falls too far behind gets
+    /// [`tokio::sync::broadcast::error::RecvError::Lagged`] on its next
+    /// `recv` rather than blocking this server's own output handling.
+    pub fn subscribe_output(&self) -> broadcast::Receiver<String> {
+        self.output.subscribe()
+    }
+
+    /// Configures a regex (with one capture group for the path) that, if
+    /// matched against a stdout line, records the crash report path
+    /// exposed via [`Self::last_crash_report`] and broadcasts
+    /// [`ServerEvent::CrashReportDetected`]; see
+    /// [`crate::mcserver_type::MCServerTypeFile::crash_report_pattern`].
+    /// Unset (the default) disables crash report detection entirely.
+    pub fn with_crash_report_pattern(mut self, crash_report_pattern: Regex) -> Self {
+        self.crash_report_pattern = Some(crash_report_pattern);
+        self
+    }
+
+    /// The path of the most recently detected crash report, if
+    /// [`Self::with_crash_report_pattern`] is configured and has matched a
+    /// stdout line. `None` until the first match.
+    pub async fn last_crash_report(&self) -> Option<PathBuf> {
+        self.last_crash_report.lock().await.clone()
+    }
+
+    /// Caps total restarts across this server's whole lifetime (not just
+    /// within a window, unlike the restart-jitter/circuit-breaker
+    /// machinery in [`crate::MCServerManager`]): once [`Self::restart`]
+    /// would take it past `max_lifetime_restarts`, it's marked
+    /// [`Status::Failed`] and excluded from
+    /// [`crate::MCServerManager::restart_crashed_servers`] instead of
+    /// restarting, until [`Self::reset_failure`] is called. Unset (the
+    /// default) leaves restarts uncapped.
+    pub fn with_max_lifetime_restarts(mut self, max_lifetime_restarts: u64) -> Self {
+        self.max_lifetime_restarts = Some(max_lifetime_restarts);
+        self
+    }
+
+    /// How many times [`Self::restart`] has actually restarted the server
+    /// since it was created (or since the last [`Self::reset_failure`]).
+    pub async fn restart_count(&self) -> u64 {
+        *self.restart_count.lock().await
+    }
+
+    /// Clears a [`Status::Failed`] server back to [`Status::Crashed`] and
+    /// zeroes its [`Self::restart_count`], making it eligible again for
+    /// [`crate::MCServerManager::restart_crashed_servers`]. A no-op if the
+    /// server isn't currently [`Status::Failed`].
+    pub async fn reset_failure(&self) {
+        let mut status = self.status.lock().await;
+        if *status != Status::Failed {
+            return;
+        }
+        *status = Status::Crashed;
+        *self.restart_count.lock().await = 0;
+    }
+
+    /// A cheap snapshot of the currently online players. Never blocks on
+    /// `add_player`/`remove_player`, so it's safe to call under heavy
+    /// join/leave churn.
+    pub fn players(&self) -> Arc<Vec<String>> {
+        self.players.load_full()
+    }
+
+    /// Like [`Self::players`], but falls back to querying RCON's `/list`
+    /// when the log-derived list is empty and [`Self::with_rcon`] is
+    /// configured. The log-derived list comes from parsing stdout, so it's
+    /// always empty for a server re-attached by
+    /// [`crate::MCServerManager::attach`] without its stdout; RCON is the
+    /// only way to see who's online there. Falls back to the (empty)
+    /// log-derived list if RCON isn't configured or the query fails.
+    pub async fn players_with_rcon_fallback(&self) -> Arc<Vec<String>> {
+        let players = self.players();
+        if !players.is_empty() || self.rcon.is_none() {
+            return players;
+        }
+
+        match self.rcon_command("list").await {
+            Ok(response) => Arc::new(Self::parse_list_response(&response)),
+            Err(err) => {
+                eprintln!("[{}] players_with_rcon_fallback: RCON '/list' failed: {err}", self.name);
+                players
+            }
+        }
+    }
+
+    /// Parses the player names out of a vanilla `/list` response, e.g.
+    /// `"There are 2 of a max of 20 players online: alice, bob"` yields
+    /// `["alice", "bob"]`. Returns an empty list for a response with no
+    /// player names after its summary sentence (a colon), including the
+    /// case of nobody online.
+    fn parse_list_response(response: &str) -> Vec<String> {
+        match response.split_once(':') {
+            Some((_, names)) => names
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn add_player(&self, name: impl Into<String>) {
+        let name = name.into();
+        self.players.rcu(|players| {
+            let mut next = (**players).clone();
+            if !next.contains(&name) {
+                next.push(name.clone());
+            }
+            next
+        });
+    }
+
+    pub fn remove_player(&self, name: &str) {
+        self.players.rcu(|players| {
+            let mut next = (**players).clone();
+            next.retain(|p| p != name);
+            next
+        });
+    }
+
+    /// Handles a stdout line reporting that `name` left the server. If
+    /// `name` was tracked as online, this is just [`Self::remove_player`].
+    /// Otherwise the manager most likely (re)started after `name` had
+    /// already connected, so by default (see
+    /// [`Self::with_strict_unknown_leave_handling`]) the anomaly is logged
+    /// and ignored rather than treated as a critical error; strict mode
+    /// restarts the server instead.
+    pub async fn handle_player_leave(&self, name: &str) -> Result<(), MCManageError> {
+        if self.players().iter().any(|player| player == name) {
+            self.remove_player(name);
+            return Ok(());
+        }
+
+        eprintln!(
+            "[{}] '{name}' left without being tracked as joined (the manager likely started after they connected)",
+            self.name
+        );
+
+        if self.strict_unknown_leave {
+            return self.restart().await;
+        }
+        Ok(())
+    }
+
+    fn matches_started(&self, line: &str) -> bool {
+        self.started_patterns.iter().any(|pattern| pattern.matches(line))
+    }
+
+    /// Reads the next line from the running child's stdout, if any,
+    /// truncated to `max_line_length`. Shared by the startup watchdog and
+    /// anything that watches for a line after the server has started, such
+    /// as [`Self::save_world`].
+    async fn next_stdout_line(&self) -> Option<String> {
+        loop {
+            let line = {
+                let mut guard = self.process.lock().await;
+                guard.as_mut()?.next_line().await.ok().flatten()?
+            };
+            let line = Self::truncate_line(line, self.max_line_length);
+            self.check_crash_report(&line).await;
+
+            match self.admit_line().await {
+                LineAdmission::Drop => continue,
+                LineAdmission::Admit => {
+                    self.write_log_line(&line).await;
+                    return Some(line);
+                }
+                LineAdmission::AdmitWithSummary(dropped) => {
+                    self.write_log_line(&format!("dropped {dropped} line(s) (rate limit exceeded)")).await;
+                    self.write_log_line(&line).await;
+                    return Some(line);
+                }
+            }
+        }
+    }
+
+    /// Checks `line` against [`Self::with_crash_report_pattern`], if
+    /// configured, recording the captured path and broadcasting
+    /// [`ServerEvent::CrashReportDetected`] on a match. A no-op without a
+    /// configured pattern or on a non-matching line.
+    async fn check_crash_report(&self, line: &str) {
+        let Some(pattern) = &self.crash_report_pattern else {
+            return;
+        };
+        let Some(path) = pattern.captures(line).and_then(|captures| captures.get(1)) else {
+            return;
+        };
+        let path = PathBuf::from(path.as_str());
+        *self.last_crash_report.lock().await = Some(path.clone());
+        let _ = self.events.send(ServerEvent::CrashReportDetected(path));
+    }
+
+    /// Decides whether the line just read should be logged/exposed, counted
+    /// and dropped, or admitted alongside a summary of however many lines
+    /// were just dropped; see [`Self::with_max_line_rate`].
+    async fn admit_line(&self) -> LineAdmission {
+        let Some(max_rate) = self.max_line_rate else {
+            return LineAdmission::Admit;
+        };
+
+        let mut window = self.line_rate_window.lock().await;
+        let now = Instant::now();
+        let window_expired = match window.started_at {
+            Some(started_at) => now.duration_since(started_at) >= Duration::from_secs(1),
+            None => true,
+        };
+
+        if window_expired {
+            let dropped = window.dropped;
+            *window = LineRateWindow {
+                started_at: Some(now),
+                admitted: 1,
+                dropped: 0,
+            };
+            return if dropped > 0 {
+                LineAdmission::AdmitWithSummary(dropped)
+            } else {
+                LineAdmission::Admit
+            };
+        }
+
+        if window.admitted < max_rate {
+            window.admitted += 1;
+            LineAdmission::Admit
+        } else {
+            window.dropped += 1;
+            LineAdmission::Drop
+        }
+    }
+
+    /// Broadcasts `line` to [`Self::subscribe_output`], then mirrors it
+    /// into the currently open log file, if any; see
+    /// [`Self::with_log_path`]. A write failure is logged rather than
+    /// propagated, since losing the on-disk mirror shouldn't bring down the
+    /// server it's mirroring.
+    async fn write_log_line(&self, line: &str) {
+        let _ = self.output.send(line.to_string());
+
+        let mut guard = self.log_file.lock().await;
+        let Some(file) = guard.as_mut() else {
+            return;
+        };
+        if let Err(err) = file.write_all(format!("{line}\n").as_bytes()).await {
+            eprintln!("[{}] could not write to the log file: {err}", self.name);
+        }
+    }
+
+    /// Opens (and, if [`Self::with_log_per_session`] is set, rotates) the
+    /// `{name}.txt` log file under [`Self::with_log_path`]'s directory. A
+    /// no-op if no log path is configured.
+    async fn open_log_file(&self) -> Result<(), MCManageError> {
+        let Some(log_path) = &self.log_path else {
+            return Ok(());
+        };
+        tokio::fs::create_dir_all(log_path).await?;
+
+        let current = log_path.join(format!("{}.txt", self.name));
+        if self.log_per_session && tokio::fs::try_exists(&current).await? {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("the system clock should be after the Unix epoch")
+                .as_millis();
+            let archived = log_path.join(format!("{}.{timestamp}.txt", self.name));
+            tokio::fs::rename(&current, &archived).await?;
+        }
+
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&current)
+            .await?;
+        *self.log_file.lock().await = Some(file);
+        Ok(())
+    }
+
+    /// Truncates `line` to at most `max_line_length` bytes, cutting at the
+    /// nearest char boundary so multibyte characters are never split, and
+    /// appends an elision marker if anything was cut.
+    fn truncate_line(line: String, max_line_length: usize) -> String {
+        if line.len() <= max_line_length {
+            return line;
+        }
+
+        let mut boundary = max_line_length;
+        while boundary > 0 && !line.is_char_boundary(boundary) {
+            boundary -= 1;
+        }
+
+        let mut truncated = line[..boundary].to_string();
+        truncated.push_str("... [truncated]");
+        truncated
+    }
+
+    /// Runs `hook` (if configured) in this server's directory and waits for
+    /// it to exit, returning an error labelled with `label` if it exits
+    /// non-zero or fails to launch. A `None` hook is a no-op.
+    async fn run_hook(&self, label: &str, hook: &Option<(String, Vec<String>)>) -> Result<(), MCManageError> {
+        let Some((command, args)) = hook else {
+            return Ok(());
+        };
+
+        let status = Command::new(command).args(args).current_dir(&self.path).status().await?;
+        if !status.success() {
+            return Err(MCManageError::NotReady(format!(
+                "'{}' {label} hook exited with {status}",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that [`Self::with_jar_path`]'s path (if configured) exists, is
+    /// a regular file and can actually be opened for reading, returning a
+    /// specific [`MCManageError::InvalidFile`] naming the path rather than
+    /// letting a missing or unreadable jar surface as an opaque spawn
+    /// failure. A server with no configured jar path is always valid.
+    fn check_jar_path(&self) -> Result<(), MCManageError> {
+        let Some(jar_path) = &self.jar_path else {
+            return Ok(());
+        };
+
+        let metadata = fs::metadata(jar_path)
+            .map_err(|err| MCManageError::InvalidFile(jar_path.clone(), format!("could not access the jar file: {err}")))?;
+        if !metadata.is_file() {
+            return Err(MCManageError::InvalidFile(
+                jar_path.clone(),
+                "expected a regular file, found a directory".to_string(),
+            ));
+        }
+        fs::File::open(jar_path)
+            .map_err(|err| MCManageError::InvalidFile(jar_path.clone(), format!("the jar file is not readable: {err}")))?;
+        Ok(())
+    }
+
+    /// Merges [`Self::with_properties_overrides`]'s keys into
+    /// `server.properties` under this server's directory, creating the
+    /// file (with just the overrides) if it doesn't exist yet. Every other
+    /// key already in the file is preserved as-is, in its original order;
+    /// overridden keys are updated in place rather than moved to the end.
+    fn apply_properties_overrides(&self) -> Result<(), MCManageError> {
+        if self.properties_overrides.is_empty() {
+            return Ok(());
+        }
+
+        let path = self.path.join("server.properties");
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let mut remaining = self.properties_overrides.clone();
+
+        let mut lines: Vec<String> = existing
+            .lines()
+            .map(|line| match line.split_once('=') {
+                Some((key, _)) if remaining.contains_key(key.trim()) => {
+                    let value = remaining.remove(key.trim()).unwrap();
+                    format!("{}={value}", key.trim())
+                }
+                _ => line.to_string(),
+            })
+            .collect();
+
+        for (key, value) in remaining {
+            lines.push(format!("{key}={value}"));
+        }
+
+        fs::create_dir_all(&self.path)?;
+        fs::write(path, lines.join("\n") + "\n")?;
+        Ok(())
+    }
+
+    /// Starts the server's process, watching stdout for the configured
+    /// started phrases. If the server doesn't reach [`Status::Started`]
+    /// within `startup_deadline`, the process is killed and a new attempt is
+    /// made, for up to `max_tries` attempts in all, before the server is marked
+    /// [`Status::Crashed`]. This prevents a server that deadlocks during
+    /// startup from looping forever.
+    ///
+    /// Checks [`Self::with_jar_path`]'s path first, if configured, then runs
+    /// [`Self::with_pre_start_command`]'s hook, then applies
+    /// [`Self::with_properties_overrides`] to `server.properties`, aborting
+    /// before Java is ever launched if any of these fail.
+    pub async fn start(&self) -> Result<(), MCManageError> {
+        *self.status.lock().await = Status::Starting;
+
+        if let Err(err) = self.check_jar_path() {
+            self.mark_crashed().await;
+            return Err(err);
+        }
+
+        if let Err(err) = self.run_hook("pre_start", &self.pre_start_command).await {
+            self.mark_crashed().await;
+            return Err(err);
+        }
+
+        if let Err(err) = self.apply_properties_overrides() {
+            self.mark_crashed().await;
+            return Err(err);
+        }
+
+        if let Err(err) = self.open_log_file().await {
+            self.mark_crashed().await;
+            return Err(err);
+        }
+
+        for attempt in 1..=self.max_tries {
+            if !self.env.is_empty() {
+                eprintln!(
+                    "[{}] starting with environment variables: {}",
+                    self.name,
+                    self.env.keys().cloned().collect::<Vec<_>>().join(", ")
+                );
+            }
+            let mut process = self
+                .runner
+                .spawn(&self.command, &self.args, &self.env, &self.path, self.kill_on_drop)?;
+
+            let started = timeout(self.startup_deadline, async {
+                loop {
+                    let Ok(Some(line)) = process.next_line().await else {
+                        return false;
+                    };
+                    let line = Self::truncate_line(line, self.max_line_length);
+                    self.write_log_line(&line).await;
+                    if self.matches_started(&line) {
+                        return true;
+                    }
+                }
+            })
+            .await
+            .unwrap_or(false);
+
+            if started {
+                *self.process.lock().await = Some(process);
+                if !self.startup_grace.is_zero() {
+                    sleep(self.startup_grace).await;
+                }
+                self.mark_started().await;
+                return Ok(());
+            }
+
+            eprintln!(
+                "[{}] startup watchdog: server did not start within {:?} (attempt {}/{})",
+                self.name, self.startup_deadline, attempt, self.max_tries
+            );
+            let _ = process.kill().await;
+        }
+
+        self.mark_crashed().await;
+        Err(MCManageError::NotReady(format!(
+            "'{}' did not report startup within {} attempt(s)",
+            self.name, self.max_tries
+        )))
+    }
+
+    /// Transitions to [`Status::Started`] and fires
+    /// [`Self::with_on_start_commands`], unless the server is already
+    /// [`Status::Started`] — guards against a server whose started phrase
+    /// matches more than once (e.g. some plugins re-log it on a reload)
+    /// re-firing the started event and re-sending the on-start commands.
+    async fn mark_started(&self) {
+        let mut status = self.status.lock().await;
+        if *status == Status::Started {
+            return;
+        }
+        *status = Status::Started;
+        drop(status);
+        let _ = self.events.send(ServerEvent::Started);
+
+        for command in &self.on_start_commands {
+            if let Err(err) = self.send_input(command).await {
+                eprintln!("[{}] on_start_commands: could not send '{command}': {err}", self.name);
+            }
+        }
+    }
+
+    /// Marks the server [`Status::Crashed`] and records
+    /// [`StopReason::FailedStart`].
+    async fn mark_crashed(&self) {
+        *self.status.lock().await = Status::Crashed;
+        *self.stop_reason.lock().await = Some(StopReason::FailedStart);
+        let _ = self.events.send(ServerEvent::Crashed);
+    }
+
+    /// Sends `input` to the server's stdin, followed by a newline, as if an
+    /// operator typed it into the console, and logs it with
+    /// `input_log_prefix` (see [`Self::with_input_log_prefix`]).
+    pub async fn send_input(&self, input: &str) -> Result<(), MCManageError> {
+        eprintln!("{}", self.input_log_line(input));
+        self.write_input(input).await
+    }
+
+    fn input_log_line(&self, input: &str) -> String {
+        format!("[{}] {}{input}", self.name, self.input_log_prefix)
+    }
+
+    /// Like [`Self::send_input`], but without logging the input at all —
+    /// for automated commands too noisy or sensitive to log.
+    pub async fn send_input_silent(&self, input: &str) -> Result<(), MCManageError> {
+        self.write_input(input).await
+    }
+
+    /// Like [`Self::send_input`], but bounded: attempts delivery within
+    /// `deadline`, restarting the server at most once if it isn't currently
+    /// accepting input rather than relying on the caller's own retry loop
+    /// running unbounded across a restart. Returns `Err(NotReady)` if
+    /// delivery can't be confirmed within `deadline`.
+    pub async fn send_input_timeout(&self, input: &str, deadline: Duration) -> Result<(), MCManageError> {
+        let attempt = timeout(deadline, async {
+            if self.write_input(input).await.is_err() {
+                self.restart().await?;
+                self.write_input(input).await?;
+            }
+            Ok(())
+        })
+        .await;
+
+        match attempt {
+            Ok(result) => {
+                if result.is_ok() {
+                    eprintln!("{}", self.input_log_line(input));
+                }
+                result
+            }
+            Err(_) => Err(MCManageError::NotReady(format!(
+                "'{}' did not confirm delivery of input within {:?}",
+                self.name, deadline
+            ))),
+        }
+    }
+
+    /// Reads `path` line by line and sends each non-empty, non-comment
+    /// (`#`-prefixed) line to the server's stdin via [`Self::send_input`],
+    /// waiting `line_delay` between lines, for scripted input such as a
+    /// scheduled sequence of admin commands.
+    pub async fn run_script(&self, path: &Path, line_delay: Duration) -> Result<(), MCManageError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let mut first = true;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !first {
+                tokio::time::sleep(line_delay).await;
+            }
+            first = false;
+            self.send_input(line).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_input(&self, input: &str) -> Result<(), MCManageError> {
+        let mut guard = self.process.lock().await;
+        let process = guard
+            .as_mut()
+            .ok_or_else(|| MCManageError::NotReady(format!("'{}' is not running", self.name)))?;
+        process.write_line(input).await?;
+        Ok(())
+    }
+
+    /// Waits up to `timeout_after` for a stdout line containing `pattern`,
+    /// returning the matching line. Generalizes the confirmation-matching
+    /// loop behind [`Self::save_world`] and [`Self::reload`] for callers that
+    /// need to wait on an arbitrary log line (e.g. a server-type-specific
+    /// event) instead of going through [`Self::next_stdout_line`] directly.
+    pub async fn wait_for_line(&self, pattern: &str, timeout_after: Duration) -> Result<String, MCManageError> {
+        let matched = timeout(timeout_after, async {
+            while let Some(line) = self.next_stdout_line().await {
+                if line.contains(pattern) {
+                    return Some(line);
+                }
+            }
+            None
+        })
+        .await
+        .ok()
+        .flatten();
+
+        matched.ok_or_else(|| {
+            MCManageError::NotReady(format!(
+                "'{}' did not produce a line matching '{pattern}' within {timeout_after:?}",
+                self.name
+            ))
+        })
+    }
+
+    /// Flushes and saves the world without stopping the server, by sending
+    /// `save_command` and waiting up to `save_timeout` for a stdout line
+    /// containing `save_confirmation_phrase` (see [`Self::with_save_command`]).
+    pub async fn save_world(&self) -> Result<(), MCManageError> {
+        self.send_input(&self.save_command).await?;
+
+        self.wait_for_line(&self.save_confirmation_phrase, self.save_timeout)
+            .await
+            .map(|_| ())
+            .map_err(|_| {
+                MCManageError::NotReady(format!(
+                    "'{}' did not confirm the world was saved within {:?}",
+                    self.name, self.save_timeout
+                ))
+            })
+    }
+
+    /// Reloads the server's configuration in place, for server types that
+    /// support it, by sending `reload_command` and waiting up to
+    /// `reload_timeout` for a stdout line containing
+    /// `reload_confirmation_phrase` (see [`Self::with_reload_command`]).
+    /// Unlike [`Self::restart`], the process is never stopped, so connected
+    /// players stay online throughout.
+    pub async fn reload(&self) -> Result<(), MCManageError> {
+        self.send_input(&self.reload_command).await?;
+
+        self.wait_for_line(&self.reload_confirmation_phrase, self.reload_timeout)
+            .await
+            .map(|_| ())
+            .map_err(|_| {
+                MCManageError::NotReady(format!(
+                    "'{}' did not confirm the reload within {:?}",
+                    self.name, self.reload_timeout
+                ))
+            })
+    }
+
+    /// Sends `gamerule {rule} {value}` to change a gamerule at runtime.
+    ///
+    /// `rule` is checked against [`KNOWN_GAMERULES`] purely to warn about a
+    /// likely typo; an unrecognized name is still sent as-is, since vanilla
+    /// adds new gamerules over time and a modded server may define its own.
+    pub async fn set_gamerule(&self, rule: &str, value: &str) -> Result<(), MCManageError> {
+        if !KNOWN_GAMERULES.contains(&rule) {
+            eprintln!("[{}] set_gamerule: '{rule}' is not a known gamerule, sending it anyway", self.name);
+        }
+        self.send_input(&format!("gamerule {rule} {value}")).await
+    }
+
+    /// Sends `gamerule {rule}` and waits up to [`Self::with_gamerule_timeout`]
+    /// for the server to echo its current value (vanilla replies with a line
+    /// containing `Gamerule {rule} is currently set to: {value}`).
+    ///
+    /// Like [`Self::set_gamerule`], `rule` is only checked against
+    /// [`KNOWN_GAMERULES`] for a warning, not rejected.
+    pub async fn get_gamerule(&self, rule: &str) -> Result<String, MCManageError> {
+        if !KNOWN_GAMERULES.contains(&rule) {
+            eprintln!("[{}] get_gamerule: '{rule}' is not a known gamerule, querying it anyway", self.name);
+        }
+        self.send_input(&format!("gamerule {rule}")).await?;
+
+        let prefix = format!("Gamerule {rule} is currently set to: ");
+        let value = timeout(self.gamerule_timeout, async {
+            while let Some(line) = self.next_stdout_line().await {
+                if let Some(pos) = line.find(&prefix) {
+                    return Some(line[pos + prefix.len()..].trim().to_string());
+                }
+            }
+            None
+        })
+        .await
+        .ok()
+        .flatten();
+
+        value.ok_or_else(|| {
+            MCManageError::NotReady(format!(
+                "'{}' did not echo the value of gamerule '{rule}' within {:?}",
+                self.name, self.gamerule_timeout
+            ))
+        })
+    }
+
+    /// Sends `command` over RCON instead of this server's stdin, e.g. for a
+    /// server launched externally (see
+    /// [`crate::mcserver_manager::MCServerManager::attach`]) whose stdin
+    /// this process never captured. Reuses the last authenticated
+    /// connection where possible.
+    ///
+    /// Returns [`MCManageError::NotReady`] if [`Self::with_rcon`] was never
+    /// called, or if the command doesn't complete within
+    /// [`Self::with_rcon_command_timeout`] — either way, any connection
+    /// involved is dropped rather than kept around for reuse, so the next
+    /// call always starts from a known-good state instead of one left
+    /// mid-exchange.
+    pub async fn rcon_command(&self, command: &str) -> Result<String, MCManageError> {
+        let (address, password) = self
+            .rcon
+            .as_ref()
+            .ok_or_else(|| MCManageError::NotReady(format!("'{}' has no RCON address/password configured", self.name)))?;
+
+        let mut guard = self.rcon_connection.lock().await;
+        let connection = match guard.take() {
+            Some(connection) => connection,
+            None => RconConnection::connect(address, password).await?,
+        };
+
+        let (connection, response) = connection.command(command, self.rcon_command_timeout).await?;
+        *guard = Some(connection);
+        Ok(response)
+    }
+
+    /// Like [`Self::stop`], but first runs [`ConcurrentClass::on_before_stop`]
+    /// (which flushes the world via [`Self::save_world`]) so in-flight
+    /// writes aren't lost.
+    pub async fn stop_gracefully(self: &Arc<Self>, reason: StopReason) -> Result<(), MCManageError> {
+        self.on_before_stop().await;
+        self.stop(reason).await
+    }
+
+    /// Stops the server's process, if any is running, then runs
+    /// [`Self::with_post_stop_command`]'s hook, if configured.
+    ///
+    /// Escalates rather than killing immediately (see
+    /// [`Self::with_stop_escalation`]): sends `stop_command` and waits for
+    /// the process to exit on its own, then (on unix) sends SIGTERM and
+    /// waits again, and only falls back to SIGKILL if the process ignores
+    /// both. This is gentler than an immediate kill and gives the server a
+    /// chance to flush world data at each rung.
+    ///
+    /// `reason` is recorded and can be read back via
+    /// [`Self::last_stop_reason`].
+    ///
+    /// Clearing the process handle here drops its stdout reader, so any
+    /// in-flight [`Self::next_stdout_line`] call elsewhere (e.g. a
+    /// concurrent [`Self::save_world`] still waiting on a confirmation line)
+    /// sees the pipe end rather than lingering on a handle to a process
+    /// that's already been reaped.
+    pub async fn stop(&self, reason: StopReason) -> Result<(), MCManageError> {
+        if self.process.lock().await.is_some() {
+            self.escalate_stop().await;
+            *self.process.lock().await = None;
+        }
+        *self.status.lock().await = Status::Stopped;
+        *self.stop_reason.lock().await = Some(reason);
+        let _ = self.events.send(ServerEvent::Stopped(reason));
+
+        if let Err(err) = self.run_hook("post_stop", &self.post_stop_command).await {
+            eprintln!("[{}] {err}", self.name);
+        }
+        Ok(())
+    }
+
+    async fn escalate_stop(&self) {
+        let _ = self.write_input(&self.stop_command).await;
+        if self.wait_for_exit(self.stop_graceful_timeout).await {
+            return;
+        }
+        eprintln!(
+            "[{}] did not stop within {:?} of '{}'",
+            self.name, self.stop_graceful_timeout, self.stop_command
+        );
+
+        #[cfg(unix)]
+        {
+            eprintln!("[{}] escalating to SIGTERM", self.name);
+            if self.send_signal(libc::SIGTERM).await && self.wait_for_exit(self.stop_term_timeout).await {
+                return;
+            }
+            eprintln!(
+                "[{}] did not stop within {:?} of SIGTERM",
+                self.name, self.stop_term_timeout
+            );
+        }
+
+        eprintln!("[{}] escalating to SIGKILL", self.name);
+        if let Some(mut process) = self.process.lock().await.take() {
+            let _ = process.kill().await;
+        }
+    }
+
+    /// Waits up to `deadline` for the running process to exit on its own,
+    /// without forcibly killing it. Returns `true` if it did (or if there's
+    /// no process to wait on).
+    async fn wait_for_exit(&self, deadline: Duration) -> bool {
+        let mut guard = self.process.lock().await;
+        let Some(process) = guard.as_mut() else {
+            return true;
+        };
+        timeout(deadline, process.wait()).await.is_ok()
+    }
+
+    /// Sends `signal` to the running process. Returns `false` without
+    /// sending anything if there's no process or its pid can't be read.
+    #[cfg(unix)]
+    async fn send_signal(&self, signal: i32) -> bool {
+        let guard = self.process.lock().await;
+        let Some(pid) = guard.as_ref().and_then(|process| process.id()) else {
+            return false;
+        };
+        // SAFETY: `kill` only reads `pid`/`signal` and has no
+        // memory-safety preconditions beyond being a valid syscall.
+        unsafe { libc::kill(pid as libc::pid_t, signal) == 0 }
+    }
+
+    /// Stops and then starts the server again.
+    ///
+    /// Marks the server [`Status::Restarting`] for the whole duration, so
+    /// callers racing a start/stop (e.g. [`crate::mcserver_manager::MCServerManager`]'s
+    /// recovery logic) can tell a transient restart from a real stop via
+    /// [`ConcurrentClass::is_restarting`].
+    pub async fn restart(&self) -> Result<(), MCManageError> {
+        if let Some(max) = self.max_lifetime_restarts {
+            let mut restart_count = self.restart_count.lock().await;
+            if *restart_count >= max {
+                *self.status.lock().await = Status::Failed;
+                let _ = self.events.send(ServerEvent::Failed);
+                return Err(MCManageError::NotReady(format!(
+                    "'{}' reached its max_lifetime_restarts limit ({max}) and is now Failed; call reset_failure to allow it to restart again",
+                    self.name
+                )));
+            }
+            *restart_count += 1;
+        }
+
+        *self.status.lock().await = Status::Restarting;
+        let _ = self.events.send(ServerEvent::Restarting);
+        self.stop(StopReason::Restart).await?;
+        self.start().await
+    }
+
+    fn eula_path(&self) -> PathBuf {
+        self.path.join("eula.txt")
+    }
+
+    /// Returns whether `eula.txt` currently records that the EULA has been accepted.
+    ///
+    /// The `eula` key is matched case-insensitively and tolerates surrounding
+    /// whitespace and both `\n` and `\r\n` line endings, rather than doing a
+    /// brittle substring search for `eula=true`.
+    pub fn has_agreed_to_eula(&self) -> Result<bool, MCManageError> {
+        let content = match fs::read_to_string(self.eula_path()) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(content.lines().any(|line| {
+            let Some((key, value)) = line.split_once('=') else {
+                return false;
+            };
+            key.trim().eq_ignore_ascii_case("eula") && value.trim().eq_ignore_ascii_case("true")
+        }))
+    }
+
+    /// Accepts the EULA on behalf of the operator if allowed to, writing
+    /// `eula.txt` under this server's directory (derived from
+    /// `Config::server_path`). [`Self::with_agree_to_eula`] takes
+    /// precedence over `Config::agree_to_eula` when set.
+    pub fn agree_to_eula(&self) -> Result<(), MCManageError> {
+        if self.has_agreed_to_eula()? {
+            return Ok(());
+        }
+        if !self.agree_to_eula_override.unwrap_or(self.config.agree_to_eula) {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.path)?;
+        fs::write(self.eula_path(), "eula=true\n")?;
+        Ok(())
+    }
+
+    /// Reads this server's `server-icon.png`, if one has been placed under
+    /// its server path, for display on a dashboard. Returns `None` if the
+    /// file doesn't exist.
+    pub fn icon(&self) -> Option<Vec<u8>> {
+        fs::read(self.path.join("server-icon.png")).ok()
+    }
+
+    /// Reads the `motd` key out of `server.properties`, if the file exists
+    /// and sets one, for display on a dashboard. Returns `None` if the file
+    /// or the key is absent.
+    pub fn motd(&self) -> Option<String> {
+        let content = fs::read_to_string(self.path.join("server.properties")).ok()?;
+        content.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            if key.trim() == "motd" {
+                Some(value.trim().to_string())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Parses `banned-players.json` under this server's directory, for
+    /// moderation dashboards. Returns an empty `Vec` if the file doesn't
+    /// exist (a server that has never banned anyone doesn't generate it).
+    pub fn banned_players(&self) -> Result<Vec<String>, MCManageError> {
+        let path = self.path.join("banned-players.json");
+        let Some(content) = Self::read_optional(&path)? else {
+            return Ok(Vec::new());
+        };
+
+        #[derive(Deserialize)]
+        struct BannedPlayer {
+            name: String,
+        }
+        let entries: Vec<BannedPlayer> =
+            serde_json::from_str(&content).map_err(|err| MCManageError::InvalidFile(path, err.to_string()))?;
+        Ok(entries.into_iter().map(|entry| entry.name).collect())
+    }
+
+    /// Parses `banned-ips.json` under this server's directory, for
+    /// moderation dashboards. Returns an empty `Vec` if the file doesn't
+    /// exist (a server that has never banned anyone doesn't generate it).
+    pub fn banned_ips(&self) -> Result<Vec<String>, MCManageError> {
+        let path = self.path.join("banned-ips.json");
+        let Some(content) = Self::read_optional(&path)? else {
+            return Ok(Vec::new());
+        };
+
+        #[derive(Deserialize)]
+        struct BannedIp {
+            ip: String,
+        }
+        let entries: Vec<BannedIp> =
+            serde_json::from_str(&content).map_err(|err| MCManageError::InvalidFile(path, err.to_string()))?;
+        Ok(entries.into_iter().map(|entry| entry.ip).collect())
+    }
+
+    /// Reads `path`, returning `None` instead of an error if it simply
+    /// doesn't exist yet; shared by [`Self::banned_players`] and
+    /// [`Self::banned_ips`].
+    fn read_optional(path: &Path) -> Result<Option<String>, MCManageError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Bans `player` by sending the `ban` console command; see
+    /// [`Self::banned_players`] to confirm it took effect.
+    pub async fn ban(&self, player: &str) -> Result<(), MCManageError> {
+        self.send_input(&format!("ban {player}")).await
+    }
+
+    /// Unbans `player` by sending the `pardon` console command; see
+    /// [`Self::banned_players`] to confirm it took effect.
+    pub async fn pardon(&self, player: &str) -> Result<(), MCManageError> {
+        self.send_input(&format!("pardon {player}")).await
+    }
+
+    /// Reads `relative_path` from under this server's directory (e.g. for a
+    /// dashboard's "view config file" or log-download feature), rejecting
+    /// any path that would escape it.
+    ///
+    /// `relative_path` is joined onto [`Self::path`] and canonicalized, then
+    /// checked to still start with the canonicalized server directory —
+    /// this rejects both `..` traversal (`"../../secret"`) and an absolute
+    /// path pointing outside the server directory, returning
+    /// [`MCManageError::InvalidValue`] either way rather than the
+    /// [`std::io::Error`] a plain `fs::read` would give for a missing file,
+    /// since a caller must not be able to distinguish "outside the sandbox"
+    /// from "doesn't exist" by error shape and go looking for the boundary.
+    pub fn read_file(&self, relative_path: impl AsRef<Path>) -> Result<Vec<u8>, MCManageError> {
+        let invalid = |reason: &str| {
+            MCManageError::InvalidValue("relative_path".to_string(), format!("{}: {reason}", relative_path.as_ref().display()))
+        };
+
+        let root = fs::canonicalize(&self.path)?;
+        let requested = root.join(relative_path.as_ref());
+        let resolved = fs::canonicalize(&requested).map_err(|_| invalid("no such file"))?;
+
+        if !resolved.starts_with(&root) {
+            return Err(invalid("escapes the server directory"));
+        }
+
+        fs::read(resolved).map_err(MCManageError::from)
+    }
+
+    /// Detaches the running process from this handle so it keeps running
+    /// even after this [`MCServer`] is dropped, regardless of
+    /// [`Self::with_kill_on_drop`]: [`Self::stop`]/[`Self::restart`] can no
+    /// longer signal, kill or wait on it either. The handle itself stays in
+    /// place, so for a spawned process its stdin pipe is kept and a later
+    /// [`Self::stop`] still writes `stop_command` to it. Returns its pid, if
+    /// it had one, so a caller (see
+    /// [`crate::mcserver_manager::MCServerManager::detach`]) can record it
+    /// for a later re-attach. Returns `None` without doing anything if the
+    /// server isn't currently running.
+    pub async fn detach(&self) -> Option<u32> {
+        let mut guard = self.process.lock().await;
+        let process = guard.as_mut()?;
+        process.detach()
+    }
+
+    /// Re-attaches this handle to an already-running process by `pid`,
+    /// recorded by an earlier [`Self::detach`]; see
+    /// [`crate::mcserver_manager::MCServerManager::attach`]. Marks the
+    /// server [`Status::Started`] without spawning anything.
+    ///
+    /// Unlike a freshly spawned process, there's no stdin/stdout pipe to
+    /// reopen for a pid this process didn't itself fork, so this only
+    /// supports status/liveness monitoring and signal delivery (stopping,
+    /// killing): [`Self::send_input`] and anything that waits on console
+    /// output will fail or simply never see a line, as documented on
+    /// [`crate::command_runner::AttachedProcess`].
+    #[cfg(unix)]
+    pub async fn attach(&self, pid: u32) {
+        *self.process.lock().await = Some(Box::new(crate::command_runner::AttachedProcess::new(pid)));
+        *self.status.lock().await = Status::Started;
+    }
+}
+
+impl ConcurrentClass for MCServer {
+    /// Flushes the world via [`Self::save_world`] before the main stop path
+    /// tears the process down, so a stop doesn't lose unsaved progress.
+    async fn on_before_stop(self: &Arc<Self>) {
+        if let Err(err) = self.save_world().await {
+            eprintln!("[{}] on_before_stop: could not save the world before stopping: {err}", self.name);
+        }
+    }
+
+    /// True for the whole duration of [`Self::restart`], from the moment
+    /// it's called until the server has fully come back up (or failed to).
+    async fn is_restarting(self: &Arc<Self>) -> bool {
+        *self.status.lock().await == Status::Restarting
+    }
+
+    /// Run by [`ConcurrentClass::stop_with_timeout`] when [`Self::stop`] or
+    /// [`Self::stop_gracefully`] didn't finish in time, e.g. because the
+    /// process refused to die even after escalating to SIGKILL. Drops the
+    /// process handle without waiting on it again and marks the server
+    /// [`Status::Crashed`], so it isn't left reporting a status that no
+    /// longer reflects reality.
+    async fn reset(self: &Arc<Self>) {
+        self.process.lock().await.take();
+        *self.status.lock().await = Status::Crashed;
+        *self.stop_reason.lock().await = Some(StopReason::Crash);
+    }
+
+    /// `alive` reflects whether a process handle is currently held, i.e.
+    /// whether [`Self::start`] has succeeded and [`Self::stop`]/[`Self::reset`]
+    /// haven't since torn it down.
+    async fn diagnostics(self: &Arc<Self>) -> Diagnostics {
+        Diagnostics {
+            name: self.name.clone(),
+            status: format!("{:?}", self.status().await),
+            alive: self.process.lock().await.is_some(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::pin::Pin;
+    use tempfile::tempdir;
+
+    /// A canned [`ManagedProcess`] that never spawns anything: it just
+    /// hands out a fixed queue of stdout lines and records whatever gets
+    /// written to its "stdin", so tests can exercise [`MCServer`]'s
+    /// start/stop/parsing logic without Java, a shell, or the network; see
+    /// [`FakeCommandRunner`].
+    struct FakeProcess {
+        lines: VecDeque<String>,
+        pub(super) stopped: bool,
+        /// Every line handed to [`Self::write_line`], in order, so tests can
+        /// assert on what was sent to "stdin" without a real process.
+        written: Arc<std::sync::Mutex<Vec<String>>>,
+        /// Mirrors what `tokio::process::Command::kill_on_drop` would do to
+        /// a real child: flipped to `true` by [`Drop`] iff `kill_on_drop`
+        /// was requested, so tests can assert a dropped [`MCServer`]
+        /// terminates (or doesn't) its process without spawning one.
+        kill_on_drop: bool,
+        killed: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl Drop for FakeProcess {
+        fn drop(&mut self) {
+            if self.kill_on_drop {
+                self.killed.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+    }
+
+    impl ManagedProcess for FakeProcess {
+        fn id(&self) -> Option<u32> {
+            None
+        }
+
+        fn detach(&mut self) -> Option<u32> {
+            self.kill_on_drop = false;
+            None
+        }
+
+        fn write_line<'a>(&'a mut self, line: &'a str) -> Pin<Box<dyn Future<Output = std::io::Result<()>> + Send + 'a>> {
+            self.stopped = true;
+            self.written.lock().unwrap().push(line.to_string());
+            Box::pin(async { Ok(()) })
+        }
+
+        fn next_line(&mut self) -> Pin<Box<dyn Future<Output = std::io::Result<Option<String>>> + Send + '_>> {
+            let line = self.lines.pop_front();
+            Box::pin(async move { Ok(line) })
+        }
+
+        fn kill(&mut self) -> Pin<Box<dyn Future<Output = std::io::Result<()>> + Send + '_>> {
+            self.stopped = true;
+            Box::pin(async { Ok(()) })
+        }
+
+        fn wait(&mut self) -> Pin<Box<dyn Future<Output = std::io::Result<()>> + Send + '_>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// A [`CommandRunner`] that ignores the requested command entirely and
+    /// hands out a [`FakeProcess`] emitting `lines`, one per call to
+    /// [`MCServer::start`]'s watchdog (and anything that reads stdout
+    /// afterwards).
+    struct FakeCommandRunner {
+        lines: Vec<String>,
+        written: Arc<std::sync::Mutex<Vec<String>>>,
+        /// Shared with every [`FakeProcess`] this runner hands out, so a
+        /// test can check whether the most recently spawned one was killed
+        /// on drop; defaults to untouched (`false`) for tests that don't
+        /// care.
+        killed: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl CommandRunner for FakeCommandRunner {
+        fn spawn(
+            &self,
+            _command: &str,
+            _args: &[String],
+            _envs: &HashMap<String, String>,
+            _current_dir: &Path,
+            kill_on_drop: bool,
+        ) -> std::io::Result<Box<dyn ManagedProcess>> {
+            Ok(Box::new(FakeProcess {
+                lines: self.lines.clone().into(),
+                stopped: false,
+                written: Arc::clone(&self.written),
+                kill_on_drop,
+                killed: Arc::clone(&self.killed),
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn fake_runner_drives_start_join_leave_stop_entirely_offline() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into(), "Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+
+        server.add_player("steve");
+        assert_eq!(server.players().as_slice(), &["steve".to_string()]);
+
+        server.handle_player_leave("steve").await.unwrap();
+        assert!(server.players().is_empty());
+
+        server.stop(StopReason::Operator).await.unwrap();
+        assert_eq!(server.status().await, Status::Stopped);
+    }
+
+    #[tokio::test]
+    async fn on_start_commands_are_sent_in_order_after_the_started_line_is_detected() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_on_start_commands(vec![
+                "gamerule doDaylightCycle false".into(),
+                "difficulty hard".into(),
+            ])
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into(), "Done".into()],
+                written: Arc::clone(&written),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+
+        assert_eq!(
+            *written.lock().unwrap(),
+            vec!["gamerule doDaylightCycle false".to_string(), "difficulty hard".to_string()]
+        );
+    }
+
+    #[test]
+    fn configured_memory_mb_parses_the_gigabyte_megabyte_and_bare_byte_forms_of_xmx() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+
+        let gigabytes = MCServer::new("survival", Arc::clone(&config)).with_command("java", vec!["-Xmx2G".into()]);
+        assert_eq!(gigabytes.configured_memory_mb(), Some(2048));
+
+        let megabytes = MCServer::new("survival", Arc::clone(&config)).with_command("java", vec!["-Xmx512m".into()]);
+        assert_eq!(megabytes.configured_memory_mb(), Some(512));
+
+        let bytes = MCServer::new("survival", Arc::clone(&config)).with_command("java", vec!["-Xmx1048576".into()]);
+        assert_eq!(bytes.configured_memory_mb(), Some(1));
+
+        let unset = MCServer::new("survival", config).with_command("java", vec![]);
+        assert_eq!(unset.configured_memory_mb(), None);
+    }
+
+    #[tokio::test]
+    async fn zero_startup_grace_transitions_to_started_immediately() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_startup_grace(Duration::ZERO)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into(), "Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn configured_startup_grace_delays_the_started_transition() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("survival", config)
+                .with_command("this-binary-does-not-exist", vec![])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_startup_grace(Duration::from_millis(200))
+                .with_command_runner(FakeCommandRunner {
+                    lines: vec!["Starting up...".into(), "Done".into()],
+                    written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                    killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                }),
+        );
+
+        let start_handle = {
+            let server = Arc::clone(&server);
+            tokio::spawn(async move { server.start().await })
+        };
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(server.status().await, Status::Starting);
+
+        start_handle.await.unwrap().unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn mark_started_ignores_a_repeated_started_line_while_already_started() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = MCServer::new("survival", config)
+            .with_started_phrases(vec!["Done".into()])
+            .with_on_start_commands(vec!["say server ready".into()]);
+        *server.process.lock().await = Some(Box::new(FakeProcess {
+            lines: VecDeque::new(),
+            stopped: false,
+            written: Arc::clone(&written),
+            kill_on_drop: false,
+            killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }));
+
+        // Simulates the started line being seen twice (e.g. a server that
+        // re-logs it on a reload): only the first should fire the started
+        // event and send the on-start commands.
+        server.mark_started().await;
+        server.mark_started().await;
+
+        assert_eq!(server.status().await, Status::Started);
+        assert_eq!(*written.lock().unwrap(), vec!["say server ready".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn fake_runner_marks_the_server_crashed_when_the_started_phrase_never_arrives() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn last_stop_reason_is_none_before_the_first_stop_or_failed_start() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+        assert_eq!(server.last_stop_reason().await, None);
+    }
+
+    #[tokio::test]
+    async fn restart_fails_and_marks_the_server_failed_once_the_lifetime_cap_is_reached() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_max_lifetime_restarts(2)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        server.restart().await.unwrap();
+        server.restart().await.unwrap();
+        assert_eq!(server.restart_count().await, 2);
+        assert_eq!(server.status().await, Status::Started);
+
+        let result = server.restart().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.status().await, Status::Failed);
+    }
+
+    #[tokio::test]
+    async fn a_failed_server_is_skipped_by_recovery_until_reset() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("survival", config)
+                .with_command("this-binary-does-not-exist", vec![])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_max_lifetime_restarts(1)
+                .with_command_runner(FakeCommandRunner {
+                    lines: vec!["Done".into()],
+                    written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                    killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                }),
+        );
+
+        server.start().await.unwrap();
+        server.restart().await.unwrap();
+        assert!(server.restart().await.is_err());
+        assert_eq!(server.status().await, Status::Failed);
+
+        // A Failed server is neither Crashed nor restarting, so it would be
+        // skipped by recovery logic that only picks up Status::Crashed
+        // (see MCServerManager::restart_crashed_servers).
+        assert_ne!(server.status().await, Status::Crashed);
+        assert!(!server.is_restarting().await);
+
+        server.reset_failure().await;
+        assert_eq!(server.status().await, Status::Crashed);
+        assert_eq!(server.restart_count().await, 0);
+
+        server.restart().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn last_stop_reason_records_operator_for_an_explicit_stop() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        server.stop(StopReason::Operator).await.unwrap();
+        assert_eq!(server.last_stop_reason().await, Some(StopReason::Operator));
+    }
+
+    #[tokio::test]
+    async fn last_stop_reason_records_idle_shutdown_for_an_idle_stop() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        server.stop(StopReason::IdleShutdown).await.unwrap();
+        assert_eq!(server.last_stop_reason().await, Some(StopReason::IdleShutdown));
+    }
+
+    #[tokio::test]
+    async fn last_stop_reason_records_crash_when_stopped_for_that_reason() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        server.stop(StopReason::Crash).await.unwrap();
+        assert_eq!(server.last_stop_reason().await, Some(StopReason::Crash));
+    }
+
+    #[tokio::test]
+    async fn last_stop_reason_records_restart_for_the_stop_half_of_a_restart() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        server.start().await.unwrap();
+        server.restart().await.unwrap();
+        assert_eq!(server.last_stop_reason().await, Some(StopReason::Restart));
+    }
+
+    #[tokio::test]
+    async fn events_keep_arriving_on_the_original_subscriber_across_a_restart() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        let mut events = server.subscribe_events();
+
+        server.start().await.unwrap();
+        assert_eq!(events.recv().await.unwrap(), ServerEvent::Started);
+
+        server.restart().await.unwrap();
+        assert_eq!(events.recv().await.unwrap(), ServerEvent::Restarting);
+        assert_eq!(events.recv().await.unwrap(), ServerEvent::Stopped(StopReason::Restart));
+        assert_eq!(events.recv().await.unwrap(), ServerEvent::Started);
+    }
+
+    #[tokio::test]
+    async fn crash_report_pattern_captures_the_path_and_emits_an_event() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_crash_report_pattern(Regex::new(r"saved to: (\S+)").unwrap())
+            .with_command_runner(FakeCommandRunner {
+                lines: vec![
+                    "Done".into(),
+                    "This crash report has been saved to: crash-reports/crash-2024-01-01.txt".into(),
+                ],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        let mut events = server.subscribe_events();
+
+        server.start().await.unwrap();
+        assert_eq!(events.recv().await.unwrap(), ServerEvent::Started);
+
+        server.wait_for_line("saved to", Duration::from_secs(2)).await.unwrap();
+
+        assert_eq!(
+            server.last_crash_report().await,
+            Some(PathBuf::from("crash-reports/crash-2024-01-01.txt"))
+        );
+        assert_eq!(
+            events.recv().await.unwrap(),
+            ServerEvent::CrashReportDetected(PathBuf::from("crash-reports/crash-2024-01-01.txt"))
+        );
+    }
+
+    #[tokio::test]
+    async fn subscribe_output_receives_admitted_lines_in_order() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Done".into(), "first line".into(), "second line".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        let mut output = server.subscribe_output();
+
+        server.start().await.unwrap();
+        assert_eq!(output.recv().await.unwrap(), "Done");
+
+        server.wait_for_line("second line", Duration::from_secs(2)).await.unwrap();
+        assert_eq!(output.recv().await.unwrap(), "first line");
+        assert_eq!(output.recv().await.unwrap(), "second line");
+    }
+
+    #[tokio::test]
+    async fn last_stop_reason_records_failed_start_when_the_server_never_starts() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            });
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.last_stop_reason().await, Some(StopReason::FailedStart));
+    }
+
+    fn server_with_eula(contents: &str) -> (tempfile::TempDir, MCServer) {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+        fs::create_dir_all(server.path()).unwrap();
+        fs::write(server.path().join("eula.txt"), contents).unwrap();
+        (dir, server)
+    }
+
+    #[test]
+    fn recognizes_spaced_eula() {
+        let (_dir, server) = server_with_eula("eula = true\n");
+        assert!(server.has_agreed_to_eula().unwrap());
+    }
+
+    #[test]
+    fn recognizes_uppercase_eula() {
+        let (_dir, server) = server_with_eula("EULA=TRUE\n");
+        assert!(server.has_agreed_to_eula().unwrap());
+    }
+
+    #[test]
+    fn recognizes_crlf_eula() {
+        let (_dir, server) = server_with_eula("#comment\r\neula=true\r\n");
+        assert!(server.has_agreed_to_eula().unwrap());
+    }
+
+    #[test]
+    fn missing_file_is_not_agreed() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+        assert!(!server.has_agreed_to_eula().unwrap());
+    }
+
+    #[test]
+    fn agree_to_eula_writes_file_when_configured() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), true));
+        let server = MCServer::new("survival", config);
+        server.agree_to_eula().unwrap();
+        assert!(server.has_agreed_to_eula().unwrap());
+    }
+
+    #[test]
+    fn with_agree_to_eula_overrides_a_false_global_config_for_one_server() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+
+        let overridden = MCServer::new("survival", config.clone()).with_agree_to_eula(true);
+        overridden.agree_to_eula().unwrap();
+        assert!(overridden.has_agreed_to_eula().unwrap());
+
+        let default = MCServer::new("creative", config);
+        default.agree_to_eula().unwrap();
+        assert!(!default.has_agreed_to_eula().unwrap());
+    }
+
+    #[test]
+    fn icon_reads_the_configured_server_icon() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        fs::write(dir.path().join("survival/server-icon.png"), b"\x89PNG fake icon bytes").unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.icon(), Some(b"\x89PNG fake icon bytes".to_vec()));
+    }
+
+    #[test]
+    fn icon_is_none_without_a_server_icon_file() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.icon(), None);
+    }
+
+    #[test]
+    fn motd_reads_the_configured_value_from_server_properties() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        fs::write(
+            dir.path().join("survival/server.properties"),
+            "level-name=world\nmotd=Welcome to the server!\n",
+        )
+        .unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.motd(), Some("Welcome to the server!".to_string()));
+    }
+
+    #[test]
+    fn motd_is_none_without_a_server_properties_file() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.motd(), None);
+    }
+
+    #[test]
+    fn banned_players_parses_the_names_from_banned_players_json() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        fs::write(
+            dir.path().join("survival/banned-players.json"),
+            r#"[
+                {"uuid": "11111111-1111-1111-1111-111111111111", "name": "Steve", "created": "2024-01-01", "source": "Server", "expires": "forever", "reason": "Banned by an operator."},
+                {"uuid": "22222222-2222-2222-2222-222222222222", "name": "Alex", "created": "2024-01-02", "source": "Server", "expires": "forever", "reason": "Griefing."}
+            ]"#,
+        )
+        .unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.banned_players().unwrap(), vec!["Steve".to_string(), "Alex".to_string()]);
+    }
+
+    #[test]
+    fn banned_players_is_empty_without_a_banned_players_file() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.banned_players().unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn banned_ips_parses_the_ips_from_banned_ips_json() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        fs::write(
+            dir.path().join("survival/banned-ips.json"),
+            r#"[
+                {"ip": "127.0.0.1", "created": "2024-01-01", "source": "Server", "expires": "forever", "reason": "Banned by an operator."}
+            ]"#,
+        )
+        .unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.banned_ips().unwrap(), vec!["127.0.0.1".to_string()]);
+    }
+
+    #[test]
+    fn banned_ips_is_empty_without_a_banned_ips_file() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.banned_ips().unwrap(), Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn ban_and_pardon_send_the_expected_console_commands() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = MCServer::new("survival", config);
+        *server.process.lock().await = Some(Box::new(FakeProcess {
+            lines: VecDeque::new(),
+            stopped: false,
+            written: Arc::clone(&written),
+            kill_on_drop: false,
+            killed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }));
+
+        server.ban("Steve").await.unwrap();
+        server.pardon("Steve").await.unwrap();
+
+        assert_eq!(*written.lock().unwrap(), vec!["ban Steve".to_string(), "pardon Steve".to_string()]);
+    }
+
+    #[test]
+    fn read_file_returns_the_contents_of_an_in_root_path() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival/logs")).unwrap();
+        fs::write(dir.path().join("survival/logs/latest.log"), b"hello").unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.read_file("logs/latest.log").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn read_file_rejects_a_dot_dot_traversal() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        fs::write(dir.path().join("secret"), b"top secret").unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert!(matches!(server.read_file("../secret"), Err(MCManageError::InvalidValue(_, _))));
+    }
+
+    #[test]
+    fn read_file_rejects_an_absolute_path_outside_the_root() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let outside = tempdir().unwrap();
+        fs::write(outside.path().join("passwd"), b"root:x:0:0").unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert!(matches!(
+            server.read_file(outside.path().join("passwd")),
+            Err(MCManageError::InvalidValue(_, _))
+        ));
+    }
+
+    #[test]
+    fn apply_properties_overrides_updates_existing_keys_in_place_and_preserves_others() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        fs::write(
+            dir.path().join("survival/server.properties"),
+            "level-name=world\nenable-rcon=false\nmotd=Welcome!\n",
+        )
+        .unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config).with_properties_overrides(HashMap::from([
+            ("enable-rcon".to_string(), "true".to_string()),
+            ("server-port".to_string(), "25566".to_string()),
+        ]));
+
+        server.apply_properties_overrides().unwrap();
+
+        let content = fs::read_to_string(dir.path().join("survival/server.properties")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "level-name=world");
+        assert_eq!(lines[1], "enable-rcon=true");
+        assert_eq!(lines[2], "motd=Welcome!");
+        assert_eq!(lines[3], "server-port=25566");
+    }
+
+    #[test]
+    fn apply_properties_overrides_creates_the_file_when_absent() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_properties_overrides(HashMap::from([("enable-rcon".to_string(), "true".to_string())]));
+
+        server.apply_properties_overrides().unwrap();
+
+        let content = fs::read_to_string(dir.path().join("survival/server.properties")).unwrap();
+        assert_eq!(content, "enable-rcon=true\n");
+    }
+
+    #[test]
+    fn apply_properties_overrides_is_a_no_op_without_any_configured_overrides() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        server.apply_properties_overrides().unwrap();
+
+        assert!(!dir.path().join("survival/server.properties").exists());
+    }
+
+    #[tokio::test]
+    async fn startup_watchdog_kills_a_server_that_never_starts() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "sleep 5".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_millis(50), 1);
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn starts_when_any_one_candidate_phrase_matches() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo 'For help, type \"help\"'".into()])
+            .with_started_phrases(vec!["Done".into(), "For help, type \"help\"".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn players_snapshot_is_consistent_under_concurrent_churn() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(MCServer::new("survival", config));
+
+        let mut writers = Vec::new();
+        for i in 0..50 {
+            let server = Arc::clone(&server);
+            writers.push(tokio::spawn(async move {
+                let name = format!("player{i}");
+                server.add_player(&name);
+                server.remove_player(&name);
+            }));
+        }
+
+        let reader_server = Arc::clone(&server);
+        let reader = tokio::spawn(async move {
+            for _ in 0..200 {
+                let snapshot = reader_server.players();
+                // A snapshot must never contain duplicate names; this is
+                // the "consistent" guarantee `rcu` provides.
+                let mut seen = (*snapshot).clone();
+                seen.sort();
+                seen.dedup();
+                assert_eq!(seen.len(), snapshot.len());
+            }
+        });
+
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        reader.await.unwrap();
+
+        assert!(server.players().is_empty());
+    }
+
+    #[tokio::test]
+    async fn start_passes_configured_env_vars_to_the_process() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let mut env = HashMap::new();
+        env.insert("MCM_TEST_VAR".to_string(), "hello".to_string());
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo $MCM_TEST_VAR".into()])
+            .with_env(env)
+            .with_started_phrases(vec!["hello".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn save_world_returns_ok_once_the_confirmation_line_is_seen() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec!["-c".into(), "echo Done; read line; echo 'Saved the game'".into()],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_save_command("save-all flush", "Saved the game", Duration::from_secs(2));
+        server.start().await.unwrap();
+
+        server.save_world().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn wait_for_line_returns_the_first_matching_line() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec!["-c".into(), "echo Done; echo unrelated; echo 'target line here'".into()],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        let line = server.wait_for_line("target line", Duration::from_secs(2)).await.unwrap();
+        assert_eq!(line, "target line here");
+    }
+
+    #[tokio::test]
+    async fn wait_for_line_times_out_when_no_line_matches() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; echo unrelated".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        let result = server.wait_for_line("target line", Duration::from_millis(100)).await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn max_line_rate_drops_excess_lines_and_logs_a_summary_once_the_window_resets() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let logs_path = dir.path().join("logs");
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "echo Done; for i in 1 2 3 4 5 6 7 8 9 10; do echo line$i; done; sleep 1.2; echo 'Saved the game'; sleep 5"
+                        .into(),
+                ],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_save_command("save-all flush", "Saved the game", Duration::from_secs(5))
+            .with_log_path(logs_path.clone())
+            .with_max_line_rate(3);
+        server.start().await.unwrap();
+
+        server.save_world().await.unwrap();
+
+        let content = fs::read_to_string(logs_path.join("survival.txt")).unwrap();
+        let admitted: Vec<&str> = content.lines().filter(|line| line.starts_with("line")).collect();
+        assert_eq!(
+            admitted,
+            vec!["line1", "line2", "line3"],
+            "expected only the first 3 lines of the flood admitted, got:\n{content}"
+        );
+        assert!(
+            content.contains("dropped 7 line(s)"),
+            "expected a summary of the 7 dropped lines, got:\n{content}"
+        );
+    }
+
+    #[tokio::test]
+    async fn save_world_times_out_when_no_confirmation_line_arrives() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; cat".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_save_command("save-all flush", "Saved the game", Duration::from_millis(100));
+        server.start().await.unwrap();
+
+        let result = server.save_world().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn stop_unblocks_a_concurrent_reader_instead_of_leaving_it_on_a_closed_pipe() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("survival", config)
+                .with_command("sh", vec!["-c".into(), "echo Done; while true; do sleep 0.05; done".into()])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_save_command("save-all flush", "Saved the game", Duration::from_secs(5))
+                .with_stop_escalation("stop", Duration::from_millis(100), Duration::from_millis(100)),
+        );
+        server.start().await.unwrap();
+
+        // `save_world` sends its command and then loops on `next_stdout_line`
+        // waiting for a confirmation that will never arrive, so it's
+        // guaranteed to still be mid-read when `stop` runs below.
+        let reader = Arc::clone(&server);
+        let reading = tokio::spawn(async move { reader.save_world().await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        server.stop(StopReason::Operator).await.unwrap();
+
+        let result = timeout(Duration::from_secs(1), reading)
+            .await
+            .expect("the concurrent reader should have unblocked promptly once stop dropped the process handle, instead of waiting out save_world's own 5s timeout")
+            .unwrap();
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn set_gamerule_writes_the_expected_command() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; read line; echo \"got:$line\"".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        server.set_gamerule("keepInventory", "true").await.unwrap();
+
+        let line = timeout(Duration::from_secs(2), server.next_stdout_line())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "got:gamerule keepInventory true");
+    }
+
+    #[tokio::test]
+    async fn get_gamerule_parses_the_echoed_value() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "echo Done; read line; echo 'Gamerule keepInventory is currently set to: true'".into(),
+                ],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        let value = server.get_gamerule("keepInventory").await.unwrap();
+        assert_eq!(value, "true");
+    }
+
+    #[tokio::test]
+    async fn get_gamerule_times_out_when_no_value_is_echoed() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; cat".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_gamerule_timeout(Duration::from_millis(100));
+        server.start().await.unwrap();
+
+        let result = server.get_gamerule("keepInventory").await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn get_gamerule_warns_but_still_queries_an_unknown_rule() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "echo Done; read line; echo 'Gamerule totallyMadeUp is currently set to: 42'".into(),
+                ],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        let value = server.get_gamerule("totallyMadeUp").await.unwrap();
+        assert_eq!(value, "42");
+    }
+
+    /// Minimal stub of a Minecraft RCON server: accepts one connection,
+    /// authenticates any login whose password matches `password`, then
+    /// replies to every command with `response`.
+    async fn spawn_stub_rcon_server(password: &'static str, response: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        async fn read_packet(stream: &mut tokio::net::TcpStream) -> (i32, String) {
+            let mut len_buf = [0u8; 4];
+            stream.read_exact(&mut len_buf).await.unwrap();
+            let len = i32::from_le_bytes(len_buf) as usize;
+            let mut buf = vec![0u8; len];
+            stream.read_exact(&mut buf).await.unwrap();
+            let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+            let body = String::from_utf8_lossy(&buf[8..buf.len() - 2]).into_owned();
+            (id, body)
+        }
+
+        fn encode_packet(id: i32, body: &str) -> Vec<u8> {
+            let mut payload = id.to_le_bytes().to_vec();
+            payload.extend_from_slice(&0i32.to_le_bytes());
+            payload.extend_from_slice(body.as_bytes());
+            payload.extend_from_slice(&[0, 0]);
+            let mut packet = (payload.len() as i32).to_le_bytes().to_vec();
+            packet.extend_from_slice(&payload);
+            packet
+        }
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (login_id, received_password) = read_packet(&mut stream).await;
+            let reply_id = if received_password == password { login_id } else { -1 };
+            stream.write_all(&encode_packet(reply_id, "")).await.unwrap();
+            if reply_id == -1 {
+                return;
+            }
+
+            loop {
+                let mut len_buf = [0u8; 4];
+                if stream.read_exact(&mut len_buf).await.is_err() {
+                    return;
+                }
+                let len = i32::from_le_bytes(len_buf) as usize;
+                let mut buf = vec![0u8; len];
+                stream.read_exact(&mut buf).await.unwrap();
+                let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+                stream.write_all(&encode_packet(id, response)).await.unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn rcon_command_authenticates_and_returns_the_response() {
+        let addr = spawn_stub_rcon_server("secret", "There are 0/20 players online").await;
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config).with_rcon(addr, "secret");
+
+        let response = server.rcon_command("list").await.unwrap();
+        assert_eq!(response, "There are 0/20 players online");
+    }
+
+    #[tokio::test]
+    async fn rcon_command_reuses_the_connection_across_calls() {
+        let addr = spawn_stub_rcon_server("secret", "ok").await;
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config).with_rcon(addr, "secret");
+
+        assert_eq!(server.rcon_command("list").await.unwrap(), "ok");
+        assert_eq!(server.rcon_command("list").await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn players_with_rcon_fallback_parses_a_list_response_when_the_log_derived_list_is_empty() {
+        let addr = spawn_stub_rcon_server("secret", "There are 2 of a max of 20 players online: alice, bob").await;
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config).with_rcon(addr, "secret");
+
+        assert!(server.players().is_empty());
+        let players = server.players_with_rcon_fallback().await;
+        assert_eq!(players.as_slice(), &["alice".to_string(), "bob".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn players_with_rcon_fallback_prefers_the_log_derived_list_when_it_has_anyone() {
+        let addr = spawn_stub_rcon_server("secret", "There are 1 of a max of 20 players online: carol").await;
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config).with_rcon(addr, "secret");
+        server.add_player("steve");
+
+        let players = server.players_with_rcon_fallback().await;
+        assert_eq!(players.as_slice(), &["steve".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn players_with_rcon_fallback_returns_the_empty_log_derived_list_without_rcon_configured() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        let players = server.players_with_rcon_fallback().await;
+        assert!(players.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rcon_command_errors_without_rcon_configured() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        let result = server.rcon_command("list").await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn rcon_command_times_out_and_resets_the_connection_for_the_next_call() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut len_buf = [0u8; 4];
+            stream.read_exact(&mut len_buf).await.unwrap();
+            let len = i32::from_le_bytes(len_buf) as usize;
+            let mut buf = vec![0u8; len];
+            stream.read_exact(&mut buf).await.unwrap();
+            let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+            let mut reply = id.to_le_bytes().to_vec();
+            reply.extend_from_slice(&0i32.to_le_bytes());
+            reply.extend_from_slice(&[0, 0]);
+            let mut packet = (reply.len() as i32).to_le_bytes().to_vec();
+            packet.extend_from_slice(&reply);
+            stream.write_all(&packet).await.unwrap();
+
+            // Read (but never answer) the first command, holding the
+            // connection open well past the client's command timeout.
+            let mut len_buf = [0u8; 4];
+            stream.read_exact(&mut len_buf).await.unwrap();
+            let len = i32::from_le_bytes(len_buf) as usize;
+            let mut buf = vec![0u8; len];
+            stream.read_exact(&mut buf).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_rcon(addr, "secret")
+            .with_rcon_command_timeout(Duration::from_millis(50));
+
+        let result = server.rcon_command("list").await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert!(server.rcon_connection.lock().await.is_none(), "a timed-out connection must not be reused");
+    }
+
+    #[tokio::test]
+    async fn reload_returns_ok_once_the_confirmation_line_is_seen_without_killing_the_process() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec!["-c".into(), "echo Done; read line; echo 'Reload complete'; cat".into()],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_reload_command("reload confirm", "Reload complete", Duration::from_secs(2));
+        server.start().await.unwrap();
+
+        server.reload().await.unwrap();
+
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn reload_times_out_when_no_confirmation_line_arrives() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; cat".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_reload_command("reload confirm", "Reload complete", Duration::from_millis(100));
+        server.start().await.unwrap();
+
+        let result = server.reload().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn run_script_sends_each_non_comment_line_in_order_with_the_configured_delay() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; while read line; do echo \"got:$line\"; done".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        let script_path = dir.path().join("script.txt");
+        std::fs::write(&script_path, "# a comment\nsay one\n\nsay two\n").unwrap();
+
+        let start = tokio::time::Instant::now();
+        server.run_script(&script_path, Duration::from_millis(50)).await.unwrap();
+
+        let first = timeout(Duration::from_secs(2), server.next_stdout_line()).await.unwrap().unwrap();
+        let second = timeout(Duration::from_secs(2), server.next_stdout_line()).await.unwrap().unwrap();
+        assert_eq!(first, "got:say one");
+        assert_eq!(second, "got:say two");
+        assert!(
+            start.elapsed() >= Duration::from_millis(50),
+            "the configured delay between lines should have been applied"
+        );
+    }
+
+    #[tokio::test]
+    async fn run_script_errors_for_a_missing_file() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        let result = server.run_script(&dir.path().join("missing.txt"), Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(MCManageError::IOError(_))));
+    }
+
+    #[test]
+    fn input_log_line_uses_the_configured_prefix() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config).with_input_log_prefix(">>> ");
+
+        assert_eq!(server.input_log_line("say hi"), "[survival] >>> say hi");
+    }
+
+    #[tokio::test]
+    async fn send_input_silent_still_delivers_the_command() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec!["-c".into(), "echo Done; read line; echo \"got:$line\"".into()],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        server.send_input_silent("ping").await.unwrap();
+
+        let line = timeout(Duration::from_secs(2), server.next_stdout_line())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "got:ping");
+    }
+
+    #[tokio::test]
+    async fn send_input_timeout_returns_not_ready_promptly_for_a_dead_server() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "sleep 5".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(5), 1);
+        // Never started, so stdin is None: write_input fails, which
+        // triggers a restart attempt that can't possibly finish within the
+        // short deadline below.
+
+        let start = tokio::time::Instant::now();
+        let result = server.send_input_timeout("say hi", Duration::from_millis(100)).await;
+        let elapsed = start.elapsed();
+
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert!(elapsed < Duration::from_secs(1), "should fail promptly instead of waiting for the restart");
+    }
+
+    #[tokio::test]
+    async fn send_input_timeout_delivers_normally_to_a_running_server() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec!["-c".into(), "echo Done; read line; echo \"got:$line\"".into()],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        server.send_input_timeout("ping", Duration::from_secs(2)).await.unwrap();
+
+        let line = timeout(Duration::from_secs(2), server.next_stdout_line())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, "got:ping");
+    }
+
+    #[test]
+    fn truncate_line_cuts_multibyte_lines_on_a_valid_char_boundary() {
+        let line = "é".repeat(100);
+        let truncated = MCServer::truncate_line(line, 51);
+
+        assert!(truncated.is_char_boundary(truncated.len() - "... [truncated]".len()));
+        assert!(truncated.ends_with("... [truncated]"));
+        // "é" is 2 bytes; a 51-byte cap can't land on one without rounding
+        // down to the nearest whole character.
+        assert_eq!(truncated.chars().filter(|c| *c == 'é').count(), 25);
+    }
+
+    #[test]
+    fn truncate_line_leaves_short_lines_untouched() {
+        let line = "Done (12.3s)!".to_string();
+        assert_eq!(MCServer::truncate_line(line.clone(), 8192), line);
+    }
+
+    #[tokio::test]
+    async fn pre_start_command_runs_before_java_is_spawned() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_pre_start_command("sh", vec!["-c".into(), "touch sentinel".into()])
+            .with_command(
+                "sh",
+                vec!["-c".into(), "test -f sentinel && echo Done || echo Missing".into()],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn a_failing_pre_start_command_aborts_the_start() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_pre_start_command("sh", vec!["-c".into(), "exit 1".into()])
+            .with_command("sh", vec!["-c".into(), "echo Done".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn post_stop_command_runs_after_the_process_has_stopped() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; sleep 5".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_post_stop_command("sh", vec!["-c".into(), "touch stopped".into()]);
+        server.start().await.unwrap();
+
+        server.stop(StopReason::Operator).await.unwrap();
+
+        assert!(server.path().join("stopped").exists());
+    }
+
+    #[tokio::test]
+    async fn start_fails_with_invalid_file_when_the_jar_path_is_missing() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let jar_path = dir.path().join("survival").join("server.jar");
+        let server = MCServer::new("survival", config)
+            .with_jar_path(jar_path.clone())
+            .with_command("sh", vec!["-c".into(), "echo Done".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::InvalidFile(path, _)) if path == jar_path));
+        assert_eq!(server.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn start_fails_with_invalid_file_when_the_jar_path_is_a_directory() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let jar_path = dir.path().join("survival").join("server.jar");
+        fs::create_dir_all(&jar_path).unwrap();
+        let server = MCServer::new("survival", config)
+            .with_jar_path(jar_path.clone())
+            .with_command("sh", vec!["-c".into(), "echo Done".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::InvalidFile(path, _)) if path == jar_path));
+        assert_eq!(server.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn start_proceeds_when_the_jar_path_is_a_readable_file() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let jar_path = dir.path().join("survival").join("server.jar");
+        fs::write(&jar_path, b"not a real jar, just needs to exist").unwrap();
+        let server = MCServer::new("survival", config)
+            .with_jar_path(jar_path)
+            .with_command("sh", vec!["-c".into(), "echo Done".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+
+        server.start().await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn handle_player_leave_removes_a_known_player() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+        server.add_player("steve");
+
+        server.handle_player_leave("steve").await.unwrap();
+
+        assert!(server.players().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tolerant_mode_ignores_an_unknown_leave_without_restarting() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        // Bumps a counter file on every spawn, so a restart (which spawns a
+        // fresh process) is observable even though Status ends up Started
+        // either way.
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "c=$(cat counter 2>/dev/null || echo 0); echo $((c+1)) > counter; echo Done; sleep 5".into(),
+                ],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        server.handle_player_leave("steve").await.unwrap();
+
+        assert_eq!(server.status().await, Status::Started);
+        let spawns: u32 = fs::read_to_string(server.path().join("counter")).unwrap().trim().parse().unwrap();
+        assert_eq!(spawns, 1, "tolerant mode must not spawn a new process");
+    }
+
+    #[tokio::test]
+    async fn strict_mode_restarts_the_server_on_an_unknown_leave() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_strict_unknown_leave_handling(true)
+            .with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "c=$(cat counter 2>/dev/null || echo 0); echo $((c+1)) > counter; echo Done; sleep 5".into(),
+                ],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1);
+        server.start().await.unwrap();
+
+        server.handle_player_leave("steve").await.unwrap();
+
+        assert_eq!(server.status().await, Status::Started);
+        let spawns: u32 = fs::read_to_string(server.path().join("counter")).unwrap().trim().parse().unwrap();
+        assert_eq!(spawns, 2, "strict mode must restart (spawn a fresh process) on an unknown leave");
+    }
+
+    #[tokio::test]
+    async fn stop_gracefully_saves_the_world_before_stopping() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("survival", config)
+                .with_command(
+                    "sh",
+                    vec!["-c".into(), "echo Done; read line; echo 'Saved the game'".into()],
+                )
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_save_command("save-all flush", "Saved the game", Duration::from_secs(2)),
+        );
+        server.start().await.unwrap();
+
+        server.stop_gracefully(StopReason::Operator).await.unwrap();
+
+        assert_eq!(server.status().await, Status::Stopped);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn is_restarting_is_true_for_the_whole_restart_window_and_false_otherwise() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("survival", config)
+                .with_command(
+                    "sh",
+                    vec![
+                        "-c".into(),
+                        "echo Done; trap '' TERM; while true; do sleep 0.05; done".into(),
+                    ],
+                )
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_stop_escalation("stop", Duration::from_millis(200), Duration::from_millis(200)),
+        );
+        server.start().await.unwrap();
+        assert!(!server.is_restarting().await);
+
+        let restarting = Arc::clone(&server);
+        let restart = tokio::spawn(async move { restarting.restart().await });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            server.is_restarting().await,
+            "the server should still be mid-restart while escalation is in progress"
+        );
+
+        restart.await.unwrap().unwrap();
+
+        assert!(!server.is_restarting().await);
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stop_escalates_to_sigkill_when_stop_and_sigterm_are_both_ignored() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config)
+            .with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "echo Done; trap '' TERM; while true; do sleep 0.05; done".into(),
+                ],
+            )
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_stop_escalation("stop", Duration::from_millis(200), Duration::from_millis(200));
+        server.start().await.unwrap();
+
+        let before = tokio::time::Instant::now();
+        server.stop(StopReason::Operator).await.unwrap();
+        let elapsed = before.elapsed();
+
+        assert_eq!(server.status().await, Status::Stopped);
+        assert!(
+            elapsed >= Duration::from_millis(400),
+            "expected both the graceful and SIGTERM timeouts to be spent before SIGKILL, elapsed {elapsed:?}"
+        );
+        assert!(
+            elapsed < Duration::from_secs(2),
+            "escalation to SIGKILL should not take anywhere near the startup deadline, elapsed {elapsed:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn log_per_session_archives_the_previous_file_on_every_restart() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let logs_path = dir.path().join("logs");
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; sleep 5".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_stop_escalation("stop", Duration::from_millis(100), Duration::from_millis(100))
+            .with_log_path(logs_path.clone())
+            .with_log_per_session(true);
+
+        for _ in 0..3 {
+            server.start().await.unwrap();
+            server.stop(StopReason::Operator).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let mut entries: Vec<String> = fs::read_dir(&logs_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        entries.sort();
+
+        assert!(
+            entries.contains(&"survival.txt".to_string()),
+            "expected a current log file among {entries:?}"
+        );
+        let archived: Vec<&String> = entries
+            .iter()
+            .filter(|name| *name != "survival.txt" && name.starts_with("survival.") && name.ends_with(".txt"))
+            .collect();
+        assert_eq!(
+            archived.len(),
+            2,
+            "expected exactly two archived log files among {entries:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn without_log_per_session_the_same_log_file_keeps_being_appended_to() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let logs_path = dir.path().join("logs");
+        let server = MCServer::new("survival", config)
+            .with_command("sh", vec!["-c".into(), "echo Done; sleep 5".into()])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_stop_escalation("stop", Duration::from_millis(100), Duration::from_millis(100))
+            .with_log_path(logs_path.clone());
+
+        server.start().await.unwrap();
+        server.stop(StopReason::Operator).await.unwrap();
+        server.start().await.unwrap();
+        server.stop(StopReason::Operator).await.unwrap();
+
+        let entries: Vec<String> = fs::read_dir(&logs_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(entries, vec!["survival.txt".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn dropping_a_running_server_kills_its_process_by_default() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let killed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into(), "Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::clone(&killed),
+            });
+
+        server.start().await.unwrap();
+        assert!(!killed.load(std::sync::atomic::Ordering::SeqCst));
+
+        drop(server);
+        assert!(killed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn with_kill_on_drop_false_leaves_the_process_running_when_dropped() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let killed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_kill_on_drop(false)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into(), "Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::clone(&killed),
+            });
+
+        server.start().await.unwrap();
+        drop(server);
+
+        assert!(!killed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn detach_leaves_a_running_process_alive_even_with_kill_on_drop_enabled() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let killed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let server = MCServer::new("survival", config)
+            .with_command("this-binary-does-not-exist", vec![])
+            .with_started_phrases(vec!["Done".into()])
+            .with_startup_watchdog(Duration::from_secs(2), 1)
+            .with_command_runner(FakeCommandRunner {
+                lines: vec!["Starting up...".into(), "Done".into()],
+                written: Arc::new(std::sync::Mutex::new(Vec::new())),
+                killed: Arc::clone(&killed),
+            });
+
+        server.start().await.unwrap();
+        server.detach().await;
+        drop(server);
+
+        assert!(!killed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn detach_is_a_no_op_returning_none_when_nothing_is_running() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("survival")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = MCServer::new("survival", config);
+
+        assert_eq!(server.detach().await, None);
+    }
+}
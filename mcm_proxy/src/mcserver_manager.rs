@@ -0,0 +1,1880 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use mcm_misc::{Config, MCManageError, Message};
+use serde::Serialize;
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::clock::{Clock, SystemClock};
+use crate::concurrent_class::ConcurrentClass;
+use crate::mcserver::{MCServer, Status, StopReason};
+use crate::server_list::{build_mcserver, ServerListEntry};
+
+/// A boxed, shareable async handler registered via
+/// [`MCServerManager::register_command`].
+type CommandHandler =
+    Box<dyn Fn(Vec<String>) -> Pin<Box<dyn Future<Output = Result<Vec<String>, MCManageError>> + Send>> + Send + Sync>;
+
+/// A read-only, serializable view of the currently managed servers,
+/// mirroring the shape of `server_list.json` so a config editor can read,
+/// edit, and write it back; see [`MCServerManager::export_config`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerListConfig {
+    pub servers: Vec<ServerListEntry>,
+}
+
+/// A notable lifecycle event observed by [`MCServerManager`] about one of
+/// its managed servers, kept in a bounded recent-events history; see
+/// [`MCServerManager::recent_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManagerEvent {
+    /// `start_all` started this server.
+    Started(String),
+    /// This server was found [`Status::Crashed`].
+    Crashed(String),
+    /// This server was restarted, whether by [`MCServerManager::restart_server`]
+    /// or as part of crash self-healing.
+    Restarted(String),
+}
+
+/// Owns every [`MCServer`] this proxy manages and provides the targeted
+/// (as opposed to global restart-all) operations on them.
+pub struct MCServerManager {
+    servers: Mutex<HashMap<String, Arc<MCServer>>>,
+    /// Whether `start_all` should only start the servers a previous
+    /// `save_state` recorded as running, instead of starting all of them.
+    restore_state: bool,
+    /// Directory containing one log file per server name, used by
+    /// [`Self::prune_orphan_logs`]. `None` disables pruning.
+    logs_path: Option<PathBuf>,
+    /// Custom command handlers registered via [`Self::register_command`],
+    /// checked by [`Self::handle_request`] before the built-in commands.
+    custom_commands: Mutex<HashMap<String, CommandHandler>>,
+    /// The jitter window used by [`Self::restart_all`]; see
+    /// [`Self::with_restart_jitter`].
+    restart_jitter: Duration,
+    /// Source of the per-server jitter offsets used by [`Self::restart_all`].
+    /// Seeded from the system clock by default; tests seed it directly to
+    /// get deterministic, reproducible offsets.
+    jitter_rng: Mutex<fastrand::Rng>,
+    /// Set via [`Self::pause`]/[`Self::resume`]; while `true`,
+    /// [`Self::restart_all`], [`Self::restart_crashed_servers`], and
+    /// [`Self::warn_and_await_idle_shutdown`] skip their work, but
+    /// [`Self::handle_request`] keeps answering status queries normally.
+    paused: AtomicBool,
+    /// Shared budget for how many servers [`Self::restart_all`] and
+    /// [`Self::restart_crashed_servers`] may have mid-restart at once; see
+    /// [`Self::with_restart_concurrency`]. `None` (the default) leaves every
+    /// restart unbounded.
+    restart_semaphore: Option<Arc<Semaphore>>,
+    /// The per-server budget [`Self::stop_all`] gives
+    /// [`ConcurrentClass::stop_with_timeout`] before giving up on that
+    /// server and moving on; see [`Self::with_stop_timeout`].
+    stop_timeout: Duration,
+    /// The config new [`MCServer`]s are built with by [`Self::update_server`];
+    /// see [`Self::with_config`]. `None` (the default) leaves
+    /// [`Self::update_server`] unavailable.
+    config: Option<Arc<Config>>,
+    /// Recent [`ManagerEvent`]s, oldest first, capped at
+    /// [`Self::event_history_cap`]; see [`Self::recent_events`].
+    events: Mutex<VecDeque<ManagerEvent>>,
+    /// How many [`ManagerEvent`]s [`Self::events`] keeps before dropping the
+    /// oldest; see [`Self::with_event_history_cap`].
+    event_history_cap: usize,
+    /// Source of "now"/sleeps for [`Self::warn_and_await_idle_shutdown`]'s
+    /// polling loop and [`Self::restart_all`]'s jitter delay. [`SystemClock`]
+    /// by default; tests inject a [`crate::clock::MockClock`] via
+    /// [`Self::with_clock`] to drive that logic without real waiting.
+    clock: Arc<dyn Clock>,
+    /// Caps the combined `-Xmx` memory (in megabytes) [`Self::start_all`]
+    /// and [`Self::start_all_reporting`] allow across every currently
+    /// started server; see [`Self::with_max_total_memory_mb`]. `None` (the
+    /// default) leaves starts unbounded.
+    max_total_memory_mb: Option<u64>,
+}
+
+impl Default for MCServerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MCServerManager {
+    pub fn new() -> Self {
+        Self {
+            servers: Mutex::new(HashMap::new()),
+            restore_state: false,
+            logs_path: None,
+            custom_commands: Mutex::new(HashMap::new()),
+            restart_jitter: Duration::ZERO,
+            jitter_rng: Mutex::new(fastrand::Rng::with_seed(Self::random_seed())),
+            paused: AtomicBool::new(false),
+            restart_semaphore: None,
+            stop_timeout: Duration::from_secs(30),
+            config: None,
+            events: Mutex::new(VecDeque::new()),
+            event_history_cap: 100,
+            clock: Arc::new(SystemClock),
+            max_total_memory_mb: None,
+        }
+    }
+
+    /// Suspends automatic restarts and idle shutdown (see struct docs for
+    /// exactly which methods this affects) for maintenance, without
+    /// stopping the manager itself — status queries keep being served.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Restores the behavior [`Self::pause`] suspended.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    fn random_seed() -> u64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("the system clock should be after the Unix epoch")
+            .as_nanos() as u64
+    }
+
+    /// Enables restoring which servers were running across a manager
+    /// process restart; see [`Self::start_all`] and [`Self::save_state`].
+    pub fn with_restore_state(mut self, restore_state: bool) -> Self {
+        self.restore_state = restore_state;
+        self
+    }
+
+    /// Configures the logs directory pruned by [`Self::prune_orphan_logs`].
+    pub fn with_logs_path(mut self, logs_path: PathBuf) -> Self {
+        self.logs_path = Some(logs_path);
+        self
+    }
+
+    /// Configures the jitter window [`Self::restart_all`] spreads each
+    /// server's restart across, instead of restarting every server at once.
+    /// `Duration::ZERO` (the default) restarts every server immediately.
+    pub fn with_restart_jitter(mut self, restart_jitter: Duration) -> Self {
+        self.restart_jitter = restart_jitter;
+        self
+    }
+
+    /// Caps how many servers [`Self::restart_all`] and
+    /// [`Self::restart_crashed_servers`] may have mid-restart at once,
+    /// sharing one budget across both so a scheduled mass restart and
+    /// crash self-healing don't pile up restarts together. Unset (the
+    /// default) leaves every restart unbounded.
+    pub fn with_restart_concurrency(mut self, restart_concurrency: usize) -> Self {
+        self.restart_semaphore = Some(Arc::new(Semaphore::new(restart_concurrency)));
+        self
+    }
+
+    /// Configures how long [`Self::stop_all`] waits on each server before
+    /// giving up on it and moving on to the next. Defaults to 30 seconds.
+    pub fn with_stop_timeout(mut self, stop_timeout: Duration) -> Self {
+        self.stop_timeout = stop_timeout;
+        self
+    }
+
+    /// Caps how many [`ManagerEvent`]s [`Self::recent_events`] can ever
+    /// return, dropping the oldest once exceeded. Defaults to 100.
+    pub fn with_event_history_cap(mut self, event_history_cap: usize) -> Self {
+        self.event_history_cap = event_history_cap;
+        self
+    }
+
+    /// Overrides the [`Clock`] used for idle-shutdown polling and restart
+    /// jitter delays. Defaults to [`SystemClock`]; tests inject a
+    /// [`crate::clock::MockClock`] to drive that logic deterministically.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Caps the combined `-Xmx` memory (in megabytes) [`Self::start_all`]
+    /// and [`Self::start_all_reporting`] allow across every currently
+    /// started server; starting one that would push the total over this
+    /// cap is refused with [`MCManageError::NotReady`] instead. Servers
+    /// without a `-Xmx` flag (see [`MCServer::configured_memory_mb`]) don't
+    /// count against the cap and are never refused. Unset (the default)
+    /// leaves starts unbounded.
+    pub fn with_max_total_memory_mb(mut self, max_total_memory_mb: u64) -> Self {
+        self.max_total_memory_mb = Some(max_total_memory_mb);
+        self
+    }
+
+    async fn record_event(&self, event: ManagerEvent) {
+        let mut events = self.events.lock().await;
+        events.push_back(event);
+        while events.len() > self.event_history_cap {
+            events.pop_front();
+        }
+    }
+
+    /// Returns up to the `n` most recently recorded [`ManagerEvent`]s,
+    /// oldest first, for a newly-connected dashboard that wants recent
+    /// history rather than just a live stream.
+    pub async fn recent_events(&self, n: usize) -> Vec<ManagerEvent> {
+        let events = self.events.lock().await;
+        let skip = events.len().saturating_sub(n);
+        events.iter().skip(skip).cloned().collect()
+    }
+
+    /// Configures the [`Config`] [`Self::update_server`] builds replacement
+    /// [`MCServer`]s with. Unset (the default) makes [`Self::update_server`]
+    /// return [`MCManageError::NotReady`].
+    pub fn with_config(mut self, config: Arc<Config>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub async fn add_server(&self, server: Arc<MCServer>) {
+        self.servers
+            .lock()
+            .await
+            .insert(server.name().to_string(), server);
+    }
+
+    pub async fn get_mcserver(&self, name: &str) -> Option<Arc<MCServer>> {
+        self.servers.lock().await.get(name).cloned()
+    }
+
+    /// Restarts the server named `name`, returning
+    /// [`MCManageError::NotFound`] if no such server is managed.
+    pub async fn restart_server(&self, name: &str) -> Result<(), MCManageError> {
+        let server = self.get_mcserver(name).await.ok_or(MCManageError::NotFound)?;
+        server.restart().await?;
+        self.record_event(ManagerEvent::Restarted(name.to_string())).await;
+        Ok(())
+    }
+
+    /// Starts every managed server, unless [`Self::with_restore_state`] was
+    /// enabled, in which case only the servers `state_path` last recorded
+    /// as running are started (a missing state file starts none). A server
+    /// built with [`MCServer::with_enabled`]`(false)` is always skipped
+    /// here, regardless of `state_path`; it can still be started
+    /// explicitly via [`Self::restart_server`] or [`MCServer::start`].
+    pub async fn start_all(&self, state_path: &Path) -> Result<(), MCManageError> {
+        let running = if self.restore_state {
+            Self::read_state(state_path)?
+        } else {
+            None
+        };
+
+        let servers: Vec<(String, Arc<MCServer>)> =
+            self.servers.lock().await.iter().map(|(name, server)| (name.clone(), Arc::clone(server))).collect();
+
+        for (name, server) in servers {
+            if !server.enabled() {
+                continue;
+            }
+            let should_start = match &running {
+                Some(state) => state.get(&name).copied().unwrap_or(false),
+                None => true,
+            };
+            if should_start {
+                self.check_memory_budget(&name, &server).await?;
+                server.start().await?;
+                self.record_event(ManagerEvent::Started(name.clone())).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::start_all`], but never aborts on the first failing
+    /// server: every applicable server is awaited to completion, its
+    /// outcome is logged exactly like [`Self::restart_crashed_servers`]
+    /// does, and the full set of per-server outcomes is returned instead of
+    /// being collapsed into a single `Result`.
+    pub async fn start_all_reporting(
+        &self,
+        state_path: &Path,
+    ) -> Result<HashMap<String, Result<(), MCManageError>>, MCManageError> {
+        let running = if self.restore_state {
+            Self::read_state(state_path)?
+        } else {
+            None
+        };
+
+        let servers: Vec<(String, Arc<MCServer>)> =
+            self.servers.lock().await.iter().map(|(name, server)| (name.clone(), Arc::clone(server))).collect();
+
+        let mut outcomes = HashMap::new();
+        for (name, server) in servers {
+            if !server.enabled() {
+                continue;
+            }
+            let should_start = match &running {
+                Some(state) => state.get(&name).copied().unwrap_or(false),
+                None => true,
+            };
+            if !should_start {
+                continue;
+            }
+            if let Err(err) = self.check_memory_budget(&name, &server).await {
+                eprintln!("[{name}] start_all_reporting: start failed: {err}");
+                outcomes.insert(name.clone(), Err(err));
+                continue;
+            }
+            let result = server.start().await;
+            match &result {
+                Ok(()) => self.record_event(ManagerEvent::Started(name.clone())).await,
+                Err(err) => eprintln!("[{name}] start_all_reporting: start failed: {err}"),
+            }
+            outcomes.insert(name.clone(), result);
+        }
+        Ok(outcomes)
+    }
+
+    /// Refuses to start `server` if doing so would push the combined
+    /// `-Xmx` memory across every currently [`Status::Started`] managed
+    /// server over [`Self::with_max_total_memory_mb`]'s cap. A no-op if no
+    /// cap is configured, or if `server` has no parseable `-Xmx` flag.
+    async fn check_memory_budget(&self, name: &str, server: &MCServer) -> Result<(), MCManageError> {
+        let Some(cap) = self.max_total_memory_mb else {
+            return Ok(());
+        };
+        let Some(needed) = server.configured_memory_mb() else {
+            return Ok(());
+        };
+        let committed = self.committed_memory_mb().await;
+        if committed + needed > cap {
+            return Err(MCManageError::NotReady(format!(
+                "starting '{name}' needs {needed} MB but only {} MB remain of the {cap} MB max_total_memory_mb budget ({committed} MB already committed)",
+                cap.saturating_sub(committed)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sums [`MCServer::configured_memory_mb`] across every currently
+    /// [`Status::Started`] managed server; see [`Self::check_memory_budget`].
+    async fn committed_memory_mb(&self) -> u64 {
+        let mut total = 0;
+        for server in self.servers.lock().await.values() {
+            if server.status().await == Status::Started {
+                total += server.configured_memory_mb().unwrap_or(0);
+            }
+        }
+        total
+    }
+
+    /// Records which managed servers are currently [`Status::Started`] to
+    /// `state_path`, so a later `start_all` can restore the same set.
+    pub async fn save_state(&self, state_path: &Path) -> Result<(), MCManageError> {
+        let mut state = HashMap::new();
+        for (name, server) in self.servers.lock().await.iter() {
+            state.insert(name.clone(), server.status().await == Status::Started);
+        }
+        let content = serde_json::to_string_pretty(&state).expect("state serialization cannot fail");
+        crate::server_list::write_atomic(state_path, &content)?;
+        Ok(())
+    }
+
+    /// Deletes every file directly under the configured logs directory
+    /// whose name (minus extension) doesn't match a currently-managed
+    /// server, e.g. after a reload drops or renames a server. Returns the
+    /// names of the pruned servers. Does nothing if no logs path was
+    /// configured, or if the logs directory doesn't exist yet.
+    pub async fn prune_orphan_logs(&self) -> Result<Vec<String>, MCManageError> {
+        let Some(logs_path) = &self.logs_path else {
+            return Ok(Vec::new());
+        };
+
+        let managed: std::collections::HashSet<String> = self.servers.lock().await.keys().cloned().collect();
+        let entries = match fs::read_dir(logs_path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut pruned = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !managed.contains(stem) {
+                fs::remove_file(&path)?;
+                pruned.push(stem.to_string());
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Reconstructs a `server_list.json`-shaped view of the currently
+    /// managed servers from their stored parameters, for a config editor
+    /// to read, edit, and write back. Servers configured without
+    /// [`MCServer::with_server_type`] are reported with an empty
+    /// `server_type`.
+    pub async fn export_config(&self) -> ServerListConfig {
+        let mut servers: Vec<ServerListEntry> = self
+            .servers
+            .lock()
+            .await
+            .values()
+            .map(|server| ServerListEntry {
+                name: server.name().to_string(),
+                server_type: server.server_type().unwrap_or("").to_string(),
+                enabled: server.enabled(),
+                agree_to_eula: server.agree_to_eula_override(),
+                ..Default::default()
+            })
+            .collect();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+        ServerListConfig { servers }
+    }
+
+    /// Restarts every managed server currently in [`Status::Crashed`],
+    /// turning crash detection into self-healing. Each restart still goes
+    /// through the server's own startup watchdog (`max_tries`), so a
+    /// server that can't come up doesn't get restarted in a tight loop
+    /// from a single call — callers driving this on a timer get that
+    /// spacing for free. Returns the names of the servers it tried to
+    /// restart, including any whose restart failed (those failures are only
+    /// logged). A no-op (returning an empty `Vec`) while [`Self::pause`] is
+    /// in effect.
+    pub async fn restart_crashed_servers(&self) -> Vec<String> {
+        if self.is_paused() {
+            return Vec::new();
+        }
+
+        let crashed: Vec<(String, Arc<MCServer>)> = {
+            let mut crashed = Vec::new();
+            for (name, server) in self.servers.lock().await.iter() {
+                if server.status().await == Status::Crashed {
+                    crashed.push((name.clone(), Arc::clone(server)));
+                }
+            }
+            crashed
+        };
+
+        let mut restarted = Vec::new();
+        for (name, server) in crashed {
+            if server.is_restarting().await {
+                continue;
+            }
+            self.record_event(ManagerEvent::Crashed(name.clone())).await;
+            let _permit = self.acquire_restart_permit().await;
+            match server.restart().await {
+                Ok(()) => self.record_event(ManagerEvent::Restarted(name.clone())).await,
+                Err(err) => eprintln!("[{name}] restart_crashed_servers: restart failed: {err}"),
+            }
+            restarted.push(name);
+        }
+        restarted
+    }
+
+    /// Restarts every managed server, offsetting each one's restart by a
+    /// random amount within [`Self::with_restart_jitter`]'s window, so a
+    /// scheduled mass restart (e.g. from `mcserver_restart_time` firing)
+    /// doesn't bring every server up at once and spike RAM/CPU. With the
+    /// default zero-length window every server restarts immediately. A
+    /// no-op while [`Self::pause`] is in effect.
+    pub async fn restart_all(&self) {
+        if self.is_paused() {
+            return;
+        }
+
+        let servers: Vec<Arc<MCServer>> = self.servers.lock().await.values().cloned().collect();
+
+        let mut handles = Vec::with_capacity(servers.len());
+        for server in servers {
+            if server.is_restarting().await {
+                continue;
+            }
+            let delay = self.next_jitter_delay().await;
+            let semaphore = self.restart_semaphore.clone();
+            let clock = Arc::clone(&self.clock);
+            handles.push(tokio::spawn(async move {
+                clock.sleep(delay).await;
+                let _permit = match &semaphore {
+                    Some(semaphore) => Some(
+                        semaphore
+                            .acquire()
+                            .await
+                            .expect("the restart semaphore is never closed"),
+                    ),
+                    None => None,
+                };
+                if let Err(err) = server.restart().await {
+                    eprintln!("[{}] restart_all: restart failed: {err}", server.name());
+                }
+            }));
+        }
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    /// Reads bytes `[start, end)` of `name`'s current log file under
+    /// [`Self::with_logs_path`], clamped to the file's actual size, for a
+    /// streaming log viewer that seeks/paginates instead of reading the
+    /// whole file or only the tail.
+    ///
+    /// Returns [`MCManageError::NotReady`] if no logs path is configured,
+    /// and [`MCManageError::NotFound`] if `name` has no log file yet.
+    pub async fn read_log_range(&self, name: &str, start: u64, end: u64) -> Result<Vec<u8>, MCManageError> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let Some(logs_path) = &self.logs_path else {
+            return Err(MCManageError::NotReady("no logs path is configured".to_string()));
+        };
+
+        let mut file = match fs::File::open(logs_path.join(format!("{name}.txt"))) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Err(MCManageError::NotFound),
+            Err(err) => return Err(err.into()),
+        };
+
+        let size = file.metadata()?.len();
+        let start = start.min(size);
+        let end = end.clamp(start, size);
+
+        file.seek(SeekFrom::Start(start))?;
+        let mut buf = vec![0; (end - start) as usize];
+        file.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Stops every managed server, within [`Self::with_stop_timeout`]'s
+    /// budget each, so one server stuck mid-stop (e.g. a process that never
+    /// exits) can't block the others or hang the caller indefinitely. Each
+    /// server that times out is left [`Status::Crashed`] by
+    /// [`ConcurrentClass::reset`] rather than stuck reporting a stale
+    /// status.
+    pub async fn stop_all(&self) {
+        let servers: Vec<Arc<MCServer>> = self.servers.lock().await.values().cloned().collect();
+
+        let mut handles = Vec::with_capacity(servers.len());
+        for server in servers {
+            let stop_timeout = self.stop_timeout;
+            handles.push(tokio::spawn(async move {
+                let result = server
+                    .stop_with_timeout(stop_timeout, server.stop_gracefully(StopReason::Operator))
+                    .await;
+                if let Err(err) = result {
+                    eprintln!("[{}] stop_all: {err}", server.name());
+                }
+            }));
+        }
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+
+    /// Atomically swaps the managed server named `name` for one rebuilt from
+    /// `new_entry`, preserving its position under that name in
+    /// [`Self::servers`]. The old server is stopped with
+    /// [`StopReason::Operator`] before the swap; the replacement is started
+    /// afterwards if `restart_after` is true.
+    ///
+    /// Unlike [`crate::server_list::load_mcserver_list`], `new_entry.template`
+    /// is not resolved here — this method has no template map to resolve it
+    /// against — so `new_entry` must set `server_type`/`jvm_args`/`args`
+    /// directly if it needs values a template would otherwise supply.
+    ///
+    /// Returns [`MCManageError::NotReady`] if [`Self::with_config`] was never
+    /// called, and [`MCManageError::NotFound`] if `name` isn't managed.
+    pub async fn update_server(
+        &self,
+        name: &str,
+        new_entry: ServerListEntry,
+        restart_after: bool,
+    ) -> Result<(), MCManageError> {
+        let config = self
+            .config
+            .clone()
+            .ok_or_else(|| MCManageError::NotReady("no config is configured".to_string()))?;
+
+        let mut servers = self.servers.lock().await;
+        let old_server = servers.get(name).cloned().ok_or(MCManageError::NotFound)?;
+        old_server.stop(StopReason::Operator).await?;
+
+        let new_server = Arc::new(build_mcserver(name, &new_entry, None, config));
+        if restart_after {
+            new_server.start().await?;
+        }
+        servers.insert(name.to_string(), new_server);
+
+        Ok(())
+    }
+
+    /// Acquires a permit from [`Self::with_restart_concurrency`]'s budget,
+    /// if configured, held by the returned guard for the duration of a
+    /// single restart.
+    async fn acquire_restart_permit(&self) -> Option<tokio::sync::SemaphorePermit<'_>> {
+        match &self.restart_semaphore {
+            Some(semaphore) => Some(
+                semaphore
+                    .acquire()
+                    .await
+                    .expect("the restart semaphore is never closed"),
+            ),
+            None => None,
+        }
+    }
+
+    /// Draws this manager's next jitter offset, bounded by
+    /// [`Self::with_restart_jitter`]'s window. Always `Duration::ZERO` when
+    /// that window is zero-length.
+    async fn next_jitter_delay(&self) -> Duration {
+        if self.restart_jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        let bound_millis = self.restart_jitter.as_millis().max(1) as u64;
+        let offset_millis = self.jitter_rng.lock().await.u64(0..=bound_millis);
+        Duration::from_millis(offset_millis)
+    }
+
+    /// Handles a `status` request message (command `"status"`, with the
+    /// queried server's name as its only arg), responding with a message
+    /// whose args carry the server's [`Status`] and current player count,
+    /// or an error message naming the server if it isn't managed. Sender
+    /// and receiver are swapped from `request`, as a response addressed
+    /// back to whoever asked.
+    pub async fn handle_status_request(&self, request: &Message) -> Message {
+        let Some(name) = request.args().first() else {
+            return Message::error(
+                request.command(),
+                request.receiver(),
+                request.sender(),
+                vec!["status requires a server name argument".to_string()],
+            );
+        };
+
+        match self.get_mcserver(name).await {
+            Some(server) => {
+                let status = server.status().await;
+                let player_count = server.players().len();
+                Message::response(
+                    request.command(),
+                    request.receiver(),
+                    request.sender(),
+                    vec![status.to_string(), player_count.to_string()],
+                )
+            }
+            None => Message::error(
+                request.command(),
+                request.receiver(),
+                request.sender(),
+                vec![format!("no server named '{name}' is managed")],
+            ),
+        }
+    }
+
+    /// Registers a handler for `name`, so that [`Self::handle_request`]
+    /// dispatches any request whose command is `name` to it instead of the
+    /// built-in commands. Registering the same name again replaces the
+    /// previous handler, letting downstream apps add domain-specific
+    /// commands without forking this type.
+    pub async fn register_command<F, Fut>(&self, name: impl Into<String>, handler: F)
+    where
+        F: Fn(Vec<String>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<String>, MCManageError>> + Send + 'static,
+    {
+        self.custom_commands
+            .lock()
+            .await
+            .insert(name.into(), Box::new(move |args| Box::pin(handler(args))));
+    }
+
+    /// Dispatches `request` to its registered custom command handler (see
+    /// [`Self::register_command`]), checked before the built-in commands
+    /// (currently just `"status"`, handled by
+    /// [`Self::handle_status_request`]). Responds with an error message if
+    /// `request`'s command matches neither.
+    pub async fn handle_request(&self, request: &Message) -> Message {
+        // The lock is dropped before the handler future is awaited, so a
+        // slow (or recursive) custom command doesn't block every other
+        // lookup/registration for its duration.
+        let pending = self
+            .custom_commands
+            .lock()
+            .await
+            .get(request.command())
+            .map(|handler| handler(request.args().to_vec()));
+
+        if let Some(pending) = pending {
+            return match pending.await {
+                Ok(args) => Message::response(request.command(), request.receiver(), request.sender(), args),
+                Err(err) => Message::error(
+                    request.command(),
+                    request.receiver(),
+                    request.sender(),
+                    vec![err.to_string()],
+                ),
+            };
+        }
+
+        match request.command() {
+            "status" => self.handle_status_request(request).await,
+            command => Message::error(
+                request.command(),
+                request.receiver(),
+                request.sender(),
+                vec![format!("no handler registered for command '{command}'")],
+            ),
+        }
+    }
+
+    /// Broadcasts `warning_message` to every managed server (as a `say`)
+    /// and waits up to `warning_duration` before idle shutdown proceeds,
+    /// polling once a second so a player joining any managed server during
+    /// the window cancels it. Returns `true` if the wait ran out with no
+    /// arrivals (safe to shut down), or `false` if it was cancelled. Always
+    /// returns `false` without sending a warning or waiting, while
+    /// [`Self::pause`] is in effect.
+    pub async fn warn_and_await_idle_shutdown(&self, warning_message: &str, warning_duration: Duration) -> bool {
+        if self.is_paused() {
+            return false;
+        }
+
+        for server in self.servers.lock().await.values() {
+            let _ = server.send_input(&format!("say {warning_message}")).await;
+        }
+
+        let baseline = self.total_player_count().await;
+        let poll_interval = Duration::from_secs(1).min(warning_duration);
+        let mut elapsed = Duration::ZERO;
+
+        while elapsed < warning_duration {
+            self.clock.sleep(poll_interval).await;
+            elapsed += poll_interval;
+            if self.total_player_count().await > baseline {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn total_player_count(&self) -> usize {
+        let mut total = 0;
+        for server in self.servers.lock().await.values() {
+            total += server.players().len();
+        }
+        total
+    }
+
+    /// Detaches every managed server's process (see [`MCServer::detach`]) so
+    /// they keep running independently of this manager even once it's
+    /// dropped, and records each one's pid to `state_path` so a freshly
+    /// constructed manager can recognize them again via [`Self::attach`].
+    ///
+    /// Also [`Self::pause`]s this manager, since restart-on-crash and idle
+    /// shutdown no longer make sense once its processes aren't exclusively
+    /// its own to manage — that's as close to "stopping the manager" as
+    /// there is to stop, since it owns no background loop or threads of its
+    /// own beyond what an individual call spawns for its own duration.
+    pub async fn detach(&self, state_path: &Path) -> Result<(), MCManageError> {
+        self.pause();
+
+        let mut pids = HashMap::new();
+        for (name, server) in self.servers.lock().await.iter() {
+            pids.insert(name.clone(), server.detach().await);
+        }
+
+        let content = serde_json::to_string_pretty(&pids).expect("pid state serialization cannot fail");
+        crate::server_list::write_atomic(state_path, &content)?;
+        Ok(())
+    }
+
+    fn read_state(state_path: &Path) -> Result<Option<HashMap<String, bool>>, MCManageError> {
+        match fs::read_to_string(state_path) {
+            Ok(content) => {
+                let state = serde_json::from_str(&content)
+                    .map_err(|err| MCManageError::InvalidFile(state_path.to_path_buf(), err.to_string()))?;
+                Ok(Some(state))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Re-attaches every already-managed server (see [`Self::add_server`])
+    /// recorded as running in `state_path` (written by an earlier
+    /// [`Self::detach`]) to its still-running process instead of spawning a
+    /// duplicate. A recorded entry with no pid, a name that isn't among
+    /// `self.servers`, or a pid that's no longer alive is left untouched,
+    /// since there's nothing to attach it to.
+    ///
+    /// Servers must already be registered via [`Self::add_server`] before
+    /// calling this, the same as every other per-server operation on this
+    /// type.
+    #[cfg(unix)]
+    pub async fn attach(&self, state_path: &Path) -> Result<(), MCManageError> {
+        let Some(pids) = Self::read_pid_state(state_path)? else {
+            return Ok(());
+        };
+
+        let servers = self.servers.lock().await;
+        for (name, pid) in pids {
+            let Some(pid) = pid else { continue };
+            let Some(server) = servers.get(&name) else { continue };
+            // SAFETY: `kill` only reads `pid`/`signal`; signal `0` sends
+            // nothing and only reports whether `pid` still exists.
+            if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+                server.attach(pid).await;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn read_pid_state(state_path: &Path) -> Result<Option<HashMap<String, Option<u32>>>, MCManageError> {
+        match fs::read_to_string(state_path) {
+            Ok(content) => {
+                let state = serde_json::from_str(&content)
+                    .map_err(|err| MCManageError::InvalidFile(state_path.to_path_buf(), err.to_string()))?;
+                Ok(Some(state))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcserver::Status;
+    use mcm_misc::Config;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn fake_server(dir: &std::path::Path, name: &str) -> Arc<MCServer> {
+        std::fs::create_dir_all(dir.join(name)).unwrap();
+        let config = Arc::new(Config::new(dir.to_path_buf(), false));
+        Arc::new(
+            MCServer::new(name, config)
+                .with_command("sh", vec!["-c".into(), "echo Done".into()])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1),
+        )
+    }
+
+    /// Like [`fake_server`], but with a `-Xmx{xmx}` flag appended to its
+    /// args, so [`MCServer::configured_memory_mb`] reports a committed
+    /// amount; see [`MCServerManager::with_max_total_memory_mb`].
+    fn fake_server_with_xmx(dir: &std::path::Path, name: &str, xmx: &str) -> Arc<MCServer> {
+        std::fs::create_dir_all(dir.join(name)).unwrap();
+        let config = Arc::new(Config::new(dir.to_path_buf(), false));
+        Arc::new(
+            MCServer::new(name, config)
+                .with_command("sh", vec!["-c".into(), "echo Done".into(), format!("-Xmx{xmx}")])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1),
+        )
+    }
+
+    /// Like [`fake_server`], but its command never prints a started phrase,
+    /// so starting it always fails with [`MCManageError::NotReady`].
+    fn fake_failing_server(dir: &std::path::Path, name: &str) -> Arc<MCServer> {
+        std::fs::create_dir_all(dir.join(name)).unwrap();
+        let config = Arc::new(Config::new(dir.to_path_buf(), false));
+        Arc::new(
+            MCServer::new(name, config)
+                .with_command("sh", vec!["-c".into(), "exit 1".into()])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_millis(50), 1),
+        )
+    }
+
+    /// Like [`fake_server`], but built with [`MCServer::with_enabled`]`(false)`.
+    fn fake_disabled_server(dir: &std::path::Path, name: &str) -> Arc<MCServer> {
+        std::fs::create_dir_all(dir.join(name)).unwrap();
+        let config = Arc::new(Config::new(dir.to_path_buf(), false));
+        Arc::new(
+            MCServer::new(name, config)
+                .with_command("sh", vec!["-c".into(), "echo Done".into()])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_enabled(false),
+        )
+    }
+
+    /// Like [`fake_server`], but its process ignores `stop`/SIGTERM, so a
+    /// [`MCServer::restart`] takes long enough to observe while in
+    /// [`Status::Restarting`] instead of settling immediately.
+    #[cfg(unix)]
+    fn fake_slow_stopping_server(dir: &std::path::Path, name: &str) -> Arc<MCServer> {
+        std::fs::create_dir_all(dir.join(name)).unwrap();
+        let config = Arc::new(Config::new(dir.to_path_buf(), false));
+        Arc::new(
+            MCServer::new(name, config)
+                .with_command(
+                    "sh",
+                    vec![
+                        "-c".into(),
+                        "echo Done; trap '' TERM; while true; do sleep 0.05; done".into(),
+                    ],
+                )
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1)
+                .with_stop_escalation("stop", Duration::from_millis(100), Duration::from_millis(100)),
+        )
+    }
+
+    #[tokio::test]
+    async fn restart_server_cycles_a_known_server() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        manager.add_server(Arc::clone(&server)).await;
+
+        manager.restart_server("lobby").await.unwrap();
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn restart_server_errors_for_an_unknown_server() {
+        let manager = MCServerManager::new();
+        let result = manager.restart_server("missing").await;
+        assert!(matches!(result, Err(MCManageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn restart_server_records_a_restarted_event() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        manager.add_server(Arc::clone(&server)).await;
+
+        manager.restart_server("lobby").await.unwrap();
+
+        let events = manager.recent_events(10).await;
+        assert_eq!(events, vec![ManagerEvent::Restarted("lobby".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn recent_events_returns_the_most_recent_n_in_order_once_the_cap_is_exceeded() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new().with_event_history_cap(3);
+        let server = fake_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        manager.add_server(Arc::clone(&server)).await;
+
+        // Push more events than the cap by restarting several times.
+        for _ in 0..5 {
+            manager.restart_server("lobby").await.unwrap();
+        }
+
+        let events = manager.recent_events(10).await;
+        assert_eq!(
+            events,
+            vec![
+                ManagerEvent::Restarted("lobby".to_string()),
+                ManagerEvent::Restarted("lobby".to_string()),
+                ManagerEvent::Restarted("lobby".to_string()),
+            ],
+            "expected only the most recent 3 (the cap) to survive"
+        );
+    }
+
+    #[tokio::test]
+    async fn recent_events_caps_the_returned_count_to_n_even_below_the_history_cap() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_server(dir.path(), "survival");
+        lobby.start().await.unwrap();
+        survival.start().await.unwrap();
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        manager.restart_server("lobby").await.unwrap();
+        manager.restart_server("survival").await.unwrap();
+
+        let events = manager.recent_events(1).await;
+        assert_eq!(events, vec![ManagerEvent::Restarted("survival".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn start_all_only_starts_servers_marked_running_in_the_state_file() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+        std::fs::write(&state_path, r#"{"lobby": true, "survival": false}"#).unwrap();
+
+        let manager = MCServerManager::new().with_restore_state(true);
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_server(dir.path(), "survival");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        manager.start_all(&state_path).await.unwrap();
+
+        assert_eq!(lobby.status().await, Status::Started);
+        assert_eq!(survival.status().await, Status::Stopped);
+    }
+
+    #[tokio::test]
+    async fn start_all_skips_a_server_built_with_enabled_false() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let maintenance = fake_disabled_server(dir.path(), "maintenance");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&maintenance)).await;
+
+        manager.start_all(&state_path).await.unwrap();
+
+        assert_eq!(lobby.status().await, Status::Started);
+        assert_eq!(maintenance.status().await, Status::Stopped);
+
+        maintenance.start().await.unwrap();
+        assert_eq!(maintenance.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn start_all_refuses_a_server_that_would_exceed_the_max_total_memory_budget() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        // Each fits the 1024 MB budget alone, but not combined: whichever
+        // one the (unordered) manager map processes second must be refused.
+        let manager = MCServerManager::new().with_max_total_memory_mb(1024);
+        let lobby = fake_server_with_xmx(dir.path(), "lobby", "768m");
+        let survival = fake_server_with_xmx(dir.path(), "survival", "512m");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        let result = manager.start_all(&state_path).await;
+
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        let started = [lobby.status().await, survival.status().await]
+            .into_iter()
+            .filter(|status| *status == Status::Started)
+            .count();
+        assert_eq!(started, 1, "exactly one server should have started before the budget was exhausted");
+    }
+
+    #[tokio::test]
+    async fn start_all_reporting_records_a_refused_outcome_without_affecting_other_servers() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        let manager = MCServerManager::new().with_max_total_memory_mb(1024);
+        let lobby = fake_server_with_xmx(dir.path(), "lobby", "768m");
+        let survival = fake_server_with_xmx(dir.path(), "survival", "512m");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        let outcomes = manager.start_all_reporting(&state_path).await.unwrap();
+
+        let ok_count = outcomes.values().filter(|outcome| outcome.is_ok()).count();
+        let refused_count = outcomes
+            .values()
+            .filter(|outcome| matches!(outcome, Err(MCManageError::NotReady(_))))
+            .count();
+        assert_eq!(ok_count, 1, "exactly one server should have been allowed to start");
+        assert_eq!(refused_count, 1, "the other should have been refused for exceeding the budget");
+    }
+
+    #[tokio::test]
+    async fn start_all_ignores_the_memory_budget_for_a_server_without_an_xmx_flag() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        let manager = MCServerManager::new().with_max_total_memory_mb(1);
+        let lobby = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&lobby)).await;
+
+        manager.start_all(&state_path).await.unwrap();
+
+        assert_eq!(lobby.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn start_all_reporting_returns_a_per_server_outcome_for_a_mix_of_results() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_failing_server(dir.path(), "survival");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        let outcomes = manager.start_all_reporting(&state_path).await.unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes["lobby"].is_ok());
+        assert!(matches!(outcomes["survival"], Err(MCManageError::NotReady(_))));
+        assert_eq!(lobby.status().await, Status::Started);
+        assert_eq!(survival.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn start_all_reporting_skips_disabled_servers() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        let manager = MCServerManager::new();
+        let maintenance = fake_disabled_server(dir.path(), "maintenance");
+        manager.add_server(Arc::clone(&maintenance)).await;
+
+        let outcomes = manager.start_all_reporting(&state_path).await.unwrap();
+
+        assert!(outcomes.is_empty());
+        assert_eq!(maintenance.status().await, Status::Stopped);
+    }
+
+    #[tokio::test]
+    async fn save_state_records_which_servers_are_started() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".state.json");
+
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_server(dir.path(), "survival");
+        lobby.start().await.unwrap();
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        manager.save_state(&state_path).await.unwrap();
+
+        let saved: std::collections::HashMap<String, bool> =
+            serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+        assert_eq!(saved.get("lobby"), Some(&true));
+        assert_eq!(saved.get("survival"), Some(&false));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn detach_records_pids_and_leaves_the_process_running() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".detached.json");
+        fs::create_dir_all(dir.path().join("lobby")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("lobby", config)
+                .with_command("sh", vec!["-c".into(), "echo Done; sleep 5".into()])
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_secs(2), 1),
+        );
+        server.start().await.unwrap();
+
+        let manager = MCServerManager::new();
+        manager.add_server(Arc::clone(&server)).await;
+
+        manager.detach(&state_path).await.unwrap();
+
+        let pids: HashMap<String, Option<u32>> =
+            serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+        let pid = pids["lobby"].expect("a started server should have recorded a pid");
+
+        // SAFETY: signal 0 sends nothing; it only probes whether `pid` is
+        // still a live process this user can signal.
+        assert_eq!(
+            unsafe { libc::kill(pid as libc::pid_t, 0) },
+            0,
+            "the detached process should still be running"
+        );
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+    }
+
+    #[tokio::test]
+    async fn detach_pauses_the_manager() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".detached.json");
+        let manager = MCServerManager::new();
+        manager.add_server(fake_server(dir.path(), "lobby")).await;
+
+        assert!(!manager.is_paused());
+        manager.detach(&state_path).await.unwrap();
+        assert!(manager.is_paused());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn attach_marks_a_recorded_running_server_as_started_instead_of_spawning_a_duplicate() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".detached.json");
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg("sleep 5")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+
+        let mut pids = HashMap::new();
+        pids.insert("lobby".to_string(), Some(pid));
+        fs::write(&state_path, serde_json::to_string_pretty(&pids).unwrap()).unwrap();
+
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&server)).await;
+        assert_eq!(server.status().await, Status::Stopped);
+
+        manager.attach(&state_path).await.unwrap();
+
+        assert_eq!(server.status().await, Status::Started);
+
+        // SAFETY: signal 0 sends nothing; it only probes whether `pid` is
+        // still alive. The process is still the one attach observed,
+        // rather than a newly spawned duplicate, since `fake_server`'s own
+        // command was never started.
+        assert_eq!(unsafe { libc::kill(pid as libc::pid_t, 0) }, 0);
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+        let _ = child.wait();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn attach_leaves_an_unrecorded_or_dead_server_stopped() {
+        let dir = tempdir().unwrap();
+        let state_path = dir.path().join(".detached.json");
+
+        let mut pids = HashMap::new();
+        pids.insert("lobby".to_string(), None);
+        // An arbitrary pid outside any real range, so it reliably looks
+        // dead rather than (like `-1` cast from `u32::MAX`) aliasing a
+        // signal broadcast to this process's own group.
+        pids.insert("survival".to_string(), Some(999_999_999));
+        fs::write(&state_path, serde_json::to_string_pretty(&pids).unwrap()).unwrap();
+
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_server(dir.path(), "survival");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        manager.attach(&state_path).await.unwrap();
+
+        assert_eq!(lobby.status().await, Status::Stopped);
+        assert_eq!(survival.status().await, Status::Stopped);
+    }
+
+    #[tokio::test]
+    async fn prune_orphan_logs_deletes_logs_for_servers_no_longer_configured() {
+        let dir = tempdir().unwrap();
+        let logs_path = dir.path().join("logs");
+        fs::create_dir_all(&logs_path).unwrap();
+        fs::write(logs_path.join("lobby.log"), "kept").unwrap();
+        fs::write(logs_path.join("survival.log"), "orphaned").unwrap();
+
+        let manager = MCServerManager::new().with_logs_path(logs_path.clone());
+        manager.add_server(fake_server(dir.path(), "lobby")).await;
+
+        let pruned = manager.prune_orphan_logs().await.unwrap();
+
+        assert_eq!(pruned, vec!["survival".to_string()]);
+        assert!(logs_path.join("lobby.log").exists());
+        assert!(!logs_path.join("survival.log").exists());
+    }
+
+    #[tokio::test]
+    async fn prune_orphan_logs_is_a_no_op_without_a_configured_logs_path() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        manager.add_server(fake_server(dir.path(), "lobby")).await;
+
+        let pruned = manager.prune_orphan_logs().await.unwrap();
+        assert!(pruned.is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_log_range_reads_a_range_from_the_middle_of_the_file() {
+        let dir = tempdir().unwrap();
+        let logs_path = dir.path().join("logs");
+        fs::create_dir_all(&logs_path).unwrap();
+        fs::write(logs_path.join("lobby.txt"), "0123456789").unwrap();
+        let manager = MCServerManager::new().with_logs_path(logs_path);
+
+        let range = manager.read_log_range("lobby", 3, 6).await.unwrap();
+        assert_eq!(range, b"345");
+    }
+
+    #[tokio::test]
+    async fn read_log_range_clamps_an_end_past_eof() {
+        let dir = tempdir().unwrap();
+        let logs_path = dir.path().join("logs");
+        fs::create_dir_all(&logs_path).unwrap();
+        fs::write(logs_path.join("lobby.txt"), "0123456789").unwrap();
+        let manager = MCServerManager::new().with_logs_path(logs_path);
+
+        let range = manager.read_log_range("lobby", 8, 1000).await.unwrap();
+        assert_eq!(range, b"89");
+    }
+
+    #[tokio::test]
+    async fn read_log_range_returns_empty_for_an_empty_range() {
+        let dir = tempdir().unwrap();
+        let logs_path = dir.path().join("logs");
+        fs::create_dir_all(&logs_path).unwrap();
+        fs::write(logs_path.join("lobby.txt"), "0123456789").unwrap();
+        let manager = MCServerManager::new().with_logs_path(logs_path);
+
+        let range = manager.read_log_range("lobby", 4, 4).await.unwrap();
+        assert!(range.is_empty());
+
+        // A start past the end of the file is clamped the same way.
+        let range = manager.read_log_range("lobby", 1000, 2000).await.unwrap();
+        assert!(range.is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_log_range_errors_without_a_configured_logs_path() {
+        let manager = MCServerManager::new();
+        let result = manager.read_log_range("lobby", 0, 10).await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn read_log_range_errors_for_a_server_with_no_log_file_yet() {
+        let dir = tempdir().unwrap();
+        let logs_path = dir.path().join("logs");
+        fs::create_dir_all(&logs_path).unwrap();
+        let manager = MCServerManager::new().with_logs_path(logs_path);
+
+        let result = manager.read_log_range("lobby", 0, 10).await;
+        assert!(matches!(result, Err(MCManageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn export_config_round_trips_a_loaded_server_list() {
+        let dir = tempdir().unwrap();
+        let list_path = dir.path().join("server_list.json");
+        fs::write(
+            &list_path,
+            r#"{"servers": [{"name": "lobby", "server_type": "vanilla"}, {"name": "survival", "server_type": "modded"}]}"#,
+        )
+        .unwrap();
+
+        let loaded = crate::server_list::load_server_list(&list_path).unwrap();
+        let manager = MCServerManager::new();
+        for entry in loaded.values() {
+            manager
+                .add_server(Arc::new(
+                    MCServer::new(&entry.name, Arc::new(Config::new(dir.path().to_path_buf(), false)))
+                        .with_server_type(&entry.server_type),
+                ))
+                .await;
+        }
+
+        let exported = manager.export_config().await;
+        let exported_json = serde_json::to_value(&exported).unwrap();
+
+        let mut expected_servers: Vec<_> = loaded.into_values().collect();
+        expected_servers.sort_by(|a, b| a.name.cmp(&b.name));
+        let expected_json = serde_json::json!({ "servers": expected_servers });
+
+        assert_eq!(exported_json, expected_json);
+    }
+
+    #[tokio::test]
+    async fn restart_crashed_servers_restarts_a_server_flagged_crashed() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("lobby")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        // Succeeds only once a counter file (bumped on every spawn) reaches
+        // 2, so the first start times out (and is marked Crashed) while a
+        // subsequent restart succeeds.
+        let server = Arc::new(
+            MCServer::new("lobby", config).with_command(
+                "sh",
+                vec![
+                    "-c".into(),
+                    "c=$(cat counter 2>/dev/null || echo 0); c=$((c+1)); echo $c > counter; \
+                     if [ $c -ge 2 ]; then echo Done; else sleep 5; fi"
+                        .into(),
+                ],
+            ).with_started_phrases(vec!["Done".into()]).with_startup_watchdog(Duration::from_millis(100), 1),
+        );
+
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.status().await, Status::Crashed);
+
+        let manager = MCServerManager::new();
+        manager.add_server(Arc::clone(&server)).await;
+
+        let restarted = manager.restart_crashed_servers().await;
+
+        assert_eq!(restarted, vec!["lobby".to_string()]);
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn next_jitter_delay_is_bounded_but_staggered_for_a_seeded_rng() {
+        let manager = MCServerManager::new().with_restart_jitter(Duration::from_secs(10));
+        *manager.jitter_rng.lock().await = fastrand::Rng::with_seed(42);
+
+        let mut offsets = Vec::new();
+        for _ in 0..5 {
+            offsets.push(manager.next_jitter_delay().await);
+        }
+
+        for offset in &offsets {
+            assert!(*offset <= Duration::from_secs(10));
+        }
+        // A seeded RNG over a 10s window essentially never draws the exact
+        // same offset five times running, so this demonstrates the restarts
+        // really are staggered rather than simultaneous.
+        assert!(offsets.iter().collect::<std::collections::HashSet<_>>().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn next_jitter_delay_is_always_zero_without_a_configured_window() {
+        let manager = MCServerManager::new();
+        assert_eq!(manager.next_jitter_delay().await, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn restart_all_with_no_configured_jitter_restarts_every_server_immediately() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_server(dir.path(), "survival");
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        manager.restart_all().await;
+
+        assert_eq!(lobby.status().await, Status::Started);
+        assert_eq!(survival.status().await, Status::Started);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn restart_all_never_exceeds_the_configured_restart_concurrency_cap() {
+        let dir = tempdir().unwrap();
+        let manager = Arc::new(MCServerManager::new().with_restart_concurrency(2));
+        let mut servers = Vec::new();
+        for i in 0..5 {
+            let server = fake_slow_stopping_server(dir.path(), &format!("server{i}"));
+            server.start().await.unwrap();
+            manager.add_server(Arc::clone(&server)).await;
+            servers.push(server);
+        }
+
+        let restarting_manager = Arc::clone(&manager);
+        let restart_all = tokio::spawn(async move { restarting_manager.restart_all().await });
+
+        let mut max_concurrent = 0;
+        for _ in 0..40 {
+            let mut concurrent = 0;
+            for server in &servers {
+                if server.status().await == Status::Restarting {
+                    concurrent += 1;
+                }
+            }
+            max_concurrent = max_concurrent.max(concurrent);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        restart_all.await.unwrap();
+
+        assert!(
+            max_concurrent <= 2,
+            "expected at most 2 servers simultaneously Restarting, saw {max_concurrent}"
+        );
+        assert!(
+            max_concurrent > 0,
+            "the polling loop should have observed at least one restart in progress"
+        );
+        for server in &servers {
+            assert_eq!(server.status().await, Status::Started);
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_all_stops_every_managed_server() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        let survival = fake_server(dir.path(), "survival");
+        lobby.start().await.unwrap();
+        survival.start().await.unwrap();
+        manager.add_server(Arc::clone(&lobby)).await;
+        manager.add_server(Arc::clone(&survival)).await;
+
+        manager.stop_all().await;
+
+        assert_eq!(lobby.status().await, Status::Stopped);
+        assert_eq!(survival.status().await, Status::Stopped);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stop_all_resets_a_server_whose_stop_does_not_finish_within_the_configured_timeout() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new().with_stop_timeout(Duration::from_millis(20));
+        let server = fake_slow_stopping_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        manager.add_server(Arc::clone(&server)).await;
+
+        manager.stop_all().await;
+
+        assert_eq!(server.status().await, Status::Crashed);
+    }
+
+    #[tokio::test]
+    async fn update_server_replaces_the_server_with_one_built_from_the_new_entry() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let manager = MCServerManager::new().with_config(Arc::clone(&config));
+        let lobby = fake_server(dir.path(), "lobby");
+        lobby.start().await.unwrap();
+        manager.add_server(Arc::clone(&lobby)).await;
+
+        let new_entry = ServerListEntry {
+            name: "lobby".to_string(),
+            server_type: "vanilla".to_string(),
+            template: None,
+            jvm_args: vec!["-Xmx2G".into()],
+            args: vec!["nogui".into()],
+            enabled: true,
+            agree_to_eula: None,
+        };
+        manager.update_server("lobby", new_entry, false).await.unwrap();
+
+        assert_eq!(lobby.status().await, Status::Stopped);
+
+        let replaced = manager.get_mcserver("lobby").await.unwrap();
+        assert!(!Arc::ptr_eq(&lobby, &replaced));
+        assert_eq!(replaced.server_type(), Some("vanilla"));
+        assert_eq!(replaced.args(), ["-Xmx2G", "-jar", "server.jar", "nogui"]);
+        assert_eq!(replaced.status().await, Status::Stopped);
+    }
+
+    #[tokio::test]
+    async fn update_server_errors_for_an_unmanaged_server() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let manager = MCServerManager::new().with_config(config);
+
+        let result = manager
+            .update_server("missing", ServerListEntry::default(), false)
+            .await;
+
+        assert!(matches!(result, Err(MCManageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn update_server_errors_without_a_configured_config() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let lobby = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&lobby)).await;
+
+        let result = manager
+            .update_server("lobby", ServerListEntry::default(), false)
+            .await;
+
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn update_server_propagates_a_start_failure_when_restart_after_is_true() {
+        let dir = tempdir().unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let manager = MCServerManager::new().with_config(Arc::clone(&config));
+        let lobby = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&lobby)).await;
+
+        // The replacement is always launched as `java -jar server.jar`,
+        // which has no `server.jar` to run under this test's tempdir, so
+        // starting it fails — this is the same gap `load_mcserver_list`
+        // leaves unaddressed, not something introduced by `update_server`.
+        let result = manager
+            .update_server("lobby", ServerListEntry::default(), true)
+            .await;
+
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+    }
+
+    #[tokio::test]
+    async fn pause_prevents_restart_crashed_servers_from_restarting_anything() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("lobby")).unwrap();
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let server = Arc::new(
+            MCServer::new("lobby", config)
+                .with_command(
+                    "sh",
+                    vec![
+                        "-c".into(),
+                        "c=$(cat counter 2>/dev/null || echo 0); c=$((c+1)); echo $c > counter; \
+                         if [ $c -ge 2 ]; then echo Done; else sleep 5; fi"
+                            .into(),
+                    ],
+                )
+                .with_started_phrases(vec!["Done".into()])
+                .with_startup_watchdog(Duration::from_millis(100), 1),
+        );
+        let result = server.start().await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        assert_eq!(server.status().await, Status::Crashed);
+
+        let manager = MCServerManager::new();
+        manager.add_server(Arc::clone(&server)).await;
+        manager.pause();
+
+        let restarted = manager.restart_crashed_servers().await;
+
+        assert!(restarted.is_empty());
+        assert_eq!(server.status().await, Status::Crashed);
+
+        manager.resume();
+        let restarted = manager.restart_crashed_servers().await;
+        assert_eq!(restarted, vec!["lobby".to_string()]);
+        assert_eq!(server.status().await, Status::Started);
+    }
+
+    #[tokio::test]
+    async fn pause_prevents_idle_shutdown_from_warning_or_waiting() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&server)).await;
+        manager.pause();
+
+        let should_shut_down = manager
+            .warn_and_await_idle_shutdown("Shutting down soon due to inactivity", Duration::from_secs(999))
+            .await;
+
+        assert!(!should_shut_down);
+    }
+
+    #[tokio::test]
+    async fn resume_restores_idle_shutdown_behavior() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(server).await;
+        manager.pause();
+        manager.resume();
+
+        let should_shut_down = manager
+            .warn_and_await_idle_shutdown("Shutting down soon due to inactivity", Duration::from_secs(1))
+            .await;
+
+        assert!(should_shut_down);
+    }
+
+    #[tokio::test]
+    async fn idle_shutdown_proceeds_once_the_mock_clock_is_advanced_past_the_warning_duration() {
+        let dir = tempdir().unwrap();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = Arc::new(MCServerManager::new().with_clock(clock.clone()));
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(server).await;
+
+        let waiting_manager = Arc::clone(&manager);
+        let wait = tokio::spawn(async move {
+            waiting_manager
+                .warn_and_await_idle_shutdown("Shutting down soon due to inactivity", Duration::from_secs(10))
+                .await
+        });
+
+        // Give the spawned task a chance to reach its first poll before
+        // advancing the clock at all.
+        tokio::task::yield_now().await;
+        assert!(!wait.is_finished(), "should still be waiting on the mock clock");
+
+        // The polling loop sleeps in one-second steps (see
+        // `warn_and_await_idle_shutdown`), so advance one step at a time
+        // rather than jumping straight to the full warning duration, which
+        // would only satisfy the first of several sequential sleep calls.
+        for _ in 0..10 {
+            clock.advance(Duration::from_secs(1));
+            tokio::task::yield_now().await;
+        }
+        let should_shut_down = tokio::time::timeout(Duration::from_secs(1), wait)
+            .await
+            .expect("advancing the mock clock should let the wait resolve without any real waiting")
+            .unwrap();
+
+        assert!(should_shut_down);
+    }
+
+    #[tokio::test]
+    async fn idle_shutdown_is_cancelled_by_a_join_observed_between_mock_clock_advances() {
+        let dir = tempdir().unwrap();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = Arc::new(MCServerManager::new().with_clock(clock.clone()));
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&server)).await;
+
+        let waiting_manager = Arc::clone(&manager);
+        let wait = tokio::spawn(async move {
+            waiting_manager
+                .warn_and_await_idle_shutdown("Shutting down soon due to inactivity", Duration::from_secs(10))
+                .await
+        });
+
+        tokio::task::yield_now().await;
+        server.add_player("steve");
+        clock.advance(Duration::from_secs(1));
+
+        let should_shut_down = tokio::time::timeout(Duration::from_secs(1), wait).await.unwrap().unwrap();
+        assert!(!should_shut_down);
+    }
+
+    #[tokio::test]
+    async fn restart_all_jitter_delay_is_driven_by_the_mock_clock_instead_of_real_time() {
+        let dir = tempdir().unwrap();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = Arc::new(
+            MCServerManager::new()
+                .with_clock(clock.clone())
+                .with_restart_jitter(Duration::from_secs(10)),
+        );
+        *manager.jitter_rng.lock().await = fastrand::Rng::with_seed(1);
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&server)).await;
+
+        let restarting_manager = Arc::clone(&manager);
+        let restart_all = tokio::spawn(async move { restarting_manager.restart_all().await });
+
+        tokio::task::yield_now().await;
+        // No real time has passed, so without the mock clock being advanced
+        // the jittered restart must still be pending.
+        assert!(!restart_all.is_finished());
+
+        clock.advance(Duration::from_secs(10));
+        tokio::time::timeout(Duration::from_secs(1), restart_all)
+            .await
+            .expect("advancing the mock clock should let the jittered restart proceed without real waiting")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn status_queries_are_still_served_while_paused() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        manager.add_server(Arc::clone(&server)).await;
+        manager.pause();
+
+        let request = mcm_misc::Message::request("status", "runner0", "communicator", vec!["lobby".to_string()]);
+        let response = manager.handle_request(&request).await;
+
+        assert_eq!(response.message_type(), mcm_misc::MessageType::Response);
+        assert_eq!(response.args(), &["Started".to_string(), "0".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn handle_status_request_responds_with_status_and_player_count_for_a_known_server() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        server.add_player("steve");
+        manager.add_server(Arc::clone(&server)).await;
+
+        let request = mcm_misc::Message::request("status", "runner0", "communicator", vec!["lobby".to_string()]);
+        let response = manager.handle_status_request(&request).await;
+
+        assert_eq!(response.message_type(), mcm_misc::MessageType::Response);
+        assert_eq!(response.sender(), "communicator");
+        assert_eq!(response.receiver(), "runner0");
+        assert_eq!(response.args(), &["Started".to_string(), "1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn handle_status_request_errors_for_an_unknown_server() {
+        let manager = MCServerManager::new();
+
+        let request = mcm_misc::Message::request("status", "runner0", "communicator", vec!["missing".to_string()]);
+        let response = manager.handle_status_request(&request).await;
+
+        assert_eq!(response.message_type(), mcm_misc::MessageType::Error);
+        assert_eq!(response.sender(), "communicator");
+        assert_eq!(response.receiver(), "runner0");
+    }
+
+    #[tokio::test]
+    async fn handle_request_dispatches_a_registered_custom_command() {
+        let manager = MCServerManager::new();
+        manager
+            .register_command("ping", |args| async move {
+                Ok(vec!["pong".to_string(), args.join(",")])
+            })
+            .await;
+
+        let request = mcm_misc::Message::request("ping", "runner0", "communicator", vec!["a".to_string(), "b".to_string()]);
+        let response = manager.handle_request(&request).await;
+
+        assert_eq!(response.message_type(), mcm_misc::MessageType::Response);
+        assert_eq!(response.sender(), "communicator");
+        assert_eq!(response.receiver(), "runner0");
+        assert_eq!(response.args(), &["pong".to_string(), "a,b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn handle_request_falls_back_to_the_built_in_status_command() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        server.start().await.unwrap();
+        manager.add_server(Arc::clone(&server)).await;
+
+        let request = mcm_misc::Message::request("status", "runner0", "communicator", vec!["lobby".to_string()]);
+        let response = manager.handle_request(&request).await;
+
+        assert_eq!(response.message_type(), mcm_misc::MessageType::Response);
+    }
+
+    #[tokio::test]
+    async fn handle_request_errors_for_an_unregistered_and_unknown_command() {
+        let manager = MCServerManager::new();
+
+        let request = mcm_misc::Message::request("nonexistent", "runner0", "communicator", vec![]);
+        let response = manager.handle_request(&request).await;
+
+        assert_eq!(response.message_type(), mcm_misc::MessageType::Error);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_join_during_the_warning_window_cancels_the_shutdown() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(Arc::clone(&server)).await;
+
+        let warn = manager.warn_and_await_idle_shutdown("Shutting down soon due to inactivity", Duration::from_secs(10));
+        let join = async {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            server.add_player("steve");
+        };
+
+        let (should_shut_down, _) = tokio::join!(warn, join);
+        assert!(!should_shut_down);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_join_during_the_warning_window_allows_the_shutdown() {
+        let dir = tempdir().unwrap();
+        let manager = MCServerManager::new();
+        let server = fake_server(dir.path(), "lobby");
+        manager.add_server(server).await;
+
+        let should_shut_down = manager
+            .warn_and_await_idle_shutdown("Shutting down soon due to inactivity", Duration::from_secs(5))
+            .await;
+        assert!(should_shut_down);
+    }
+}
@@ -0,0 +1,652 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use mcm_misc::MCManageError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A single pattern checked against a server's stdout line to detect a
+/// condition, such as the server having finished starting.
+#[derive(Debug, Clone)]
+pub enum StartedPattern {
+    /// Matches if the line contains this literal phrase.
+    Phrase(String),
+    /// Matches if the line matches this regex.
+    Regex(Regex),
+}
+
+impl StartedPattern {
+    pub fn matches(&self, line: &str) -> bool {
+        match self {
+            StartedPattern::Phrase(phrase) => line.contains(phrase.as_str()),
+            StartedPattern::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// The on-disk shape of a single entry in `mcserver_types.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MCServerTypeFile {
+    /// Literal phrases, any ONE of which marks the server as started.
+    /// Kept for backward compatibility with configs that only had a single
+    /// phrase here.
+    #[serde(default)]
+    pub started: Vec<String>,
+    /// Regexes, any ONE of which marks the server as started.
+    #[serde(default)]
+    pub started_regex: Vec<String>,
+    /// Index (0-based) of the whitespace-separated word carrying the
+    /// player's name in a join line, e.g. `3` for `[12:34:56] [Server
+    /// thread/INFO]: Steve joined the game`. `None` (the default) disables
+    /// name extraction for this server type.
+    #[serde(default)]
+    pub player_name_pos: Option<usize>,
+    /// A literal substring (e.g. `"INFO]:"`) a line must contain before
+    /// [`MCServerType::get_player_name_joined`] extracts a name from it.
+    /// Without this, a chat message a player crafted to look like a join
+    /// line (e.g. `<Steve> Steve joined the game`) would be mistaken for a
+    /// real one; requiring the server's own log-level/tag prefix anchors
+    /// the match to lines the server itself emitted. `None` (the default)
+    /// disables the check.
+    #[serde(default)]
+    pub player_name_anchor: Option<String>,
+    /// Seconds to wait after the started line matches before reporting
+    /// [`crate::mcserver::Status::Started`]; see
+    /// [`crate::MCServer::with_startup_grace`]. `None` (the default)
+    /// transitions immediately.
+    #[serde(default)]
+    pub startup_grace_secs: Option<u64>,
+    /// A regex, with one capture group for the path, matched against
+    /// stdout to detect a crash report being saved (e.g. vanilla/Forge's
+    /// "This crash report has been saved to: ..."); see
+    /// [`crate::MCServer::with_crash_report_pattern`]. `None` (the default)
+    /// disables crash report detection for this server type.
+    #[serde(default)]
+    pub crash_report_pattern: Option<String>,
+}
+
+/// A parsed server type, keyed by name in `mcserver_types.json`.
+#[derive(Debug, Clone)]
+pub struct MCServerType {
+    pub name: String,
+    pub started_patterns: Vec<StartedPattern>,
+    /// See [`MCServerTypeFile::player_name_pos`].
+    pub player_name_pos: Option<usize>,
+    /// See [`MCServerTypeFile::player_name_anchor`].
+    pub player_name_anchor: Option<String>,
+    /// See [`MCServerTypeFile::startup_grace_secs`].
+    pub startup_grace: Duration,
+    /// See [`MCServerTypeFile::crash_report_pattern`].
+    pub crash_report_pattern: Option<Regex>,
+}
+
+impl MCServerType {
+    pub fn from_file(name: impl Into<String>, file: MCServerTypeFile) -> Result<Self, MCManageError> {
+        let mut started_patterns: Vec<StartedPattern> =
+            file.started.into_iter().map(StartedPattern::Phrase).collect();
+        for pattern in file.started_regex {
+            let regex = Regex::new(&pattern)
+                .map_err(|err| MCManageError::InvalidFile(PathBuf::new(), err.to_string()))?;
+            started_patterns.push(StartedPattern::Regex(regex));
+        }
+        let crash_report_pattern = file
+            .crash_report_pattern
+            .map(|pattern| Regex::new(&pattern))
+            .transpose()
+            .map_err(|err| MCManageError::InvalidFile(PathBuf::new(), err.to_string()))?;
+        Ok(Self {
+            name: name.into(),
+            started_patterns,
+            player_name_pos: file.player_name_pos,
+            player_name_anchor: file.player_name_anchor,
+            startup_grace: file.startup_grace_secs.map(Duration::from_secs).unwrap_or(Duration::ZERO),
+            crash_report_pattern,
+        })
+    }
+
+    /// A started line is detected if it matches ANY ONE of the configured
+    /// patterns, rather than requiring all of them to match.
+    pub fn matches_started(&self, line: &str) -> bool {
+        self.started_patterns.iter().any(|pattern| pattern.matches(line))
+    }
+
+    /// Extracts the joining player's name from `line`, assuming it's
+    /// already known to be a player-joined line, by taking the word at
+    /// [`Self::player_name_pos`]. Returns `None` rather than an error both
+    /// when no position is configured and when `line` simply has fewer
+    /// words than that position — a short line here just means it doesn't
+    /// match this server type's expected shape, not that anything is
+    /// corrupt, so callers should skip it rather than treat it as fatal.
+    ///
+    /// If [`Self::player_name_anchor`] is configured, `line` must also
+    /// contain it, so a chat message spoofing a join line's wording
+    /// without the server's own log-level/tag prefix isn't mistaken for a
+    /// real join.
+    pub fn get_player_name_joined(&self, line: &str) -> Option<String> {
+        let pos = self.player_name_pos?;
+        if let Some(anchor) = &self.player_name_anchor {
+            if !line.contains(anchor.as_str()) {
+                return None;
+            }
+        }
+        line.split_whitespace().nth(pos).map(str::to_string)
+    }
+
+    /// Loads every entry from `mcserver_types.json` under `config_dir`.
+    ///
+    /// Reads the file via `tokio::fs` rather than blocking `std::fs`, since
+    /// this is called from async contexts and a blocking read would stall
+    /// the whole runtime thread until it completes.
+    pub async fn load_all(config_dir: &Path) -> Result<HashMap<String, MCServerType>, MCManageError> {
+        let path = config_dir.join("mcserver_types.json");
+        let content = tokio::fs::read_to_string(&path).await?;
+        let raw: HashMap<String, MCServerTypeFile> = serde_json::from_str(&content)
+            .map_err(|err| MCManageError::InvalidFile(path.clone(), err.to_string()))?;
+
+        raw.into_iter()
+            .map(|(name, file)| {
+                let server_type = MCServerType::from_file(name.clone(), file)?;
+                Ok((name, server_type))
+            })
+            .collect()
+    }
+
+    /// Looks up a single server type by name in `mcserver_types.json` under
+    /// `config_dir`.
+    ///
+    /// A missing type name returns [`MCManageError::UnknownServerType`]. A
+    /// missing or invalid file is regenerated with the embedded defaults
+    /// and the lookup is retried exactly once, UNLESS `confirm_regeneration`
+    /// is `false`, in which case the file is left exactly as it was and
+    /// [`MCManageError::InvalidFile`]/the underlying IO error is returned
+    /// instead, so an operator who wants to be asked before anything of
+    /// theirs gets overwritten can opt out of the silent regeneration.
+    /// Passing `true` preserves the previous always-regenerate behavior.
+    pub async fn get(name: &str, config_dir: &Path, confirm_regeneration: bool) -> Result<MCServerType, MCManageError> {
+        Self::get_with_retry(name, config_dir, confirm_regeneration).await
+    }
+
+    fn get_with_retry<'a>(
+        name: &'a str,
+        config_dir: &'a Path,
+        allow_regenerate: bool,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<MCServerType, MCManageError>> + 'a>> {
+        Box::pin(async move {
+            let path = config_dir.join("mcserver_types.json");
+
+            let content = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound && allow_regenerate => {
+                    Self::generate_default_file(config_dir).await?;
+                    return Self::get_with_retry(name, config_dir, false).await;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let raw: HashMap<String, MCServerTypeFile> = match serde_json::from_str(&content) {
+                Ok(raw) => raw,
+                Err(_) if allow_regenerate => {
+                    Self::generate_default_file(config_dir).await?;
+                    return Self::get_with_retry(name, config_dir, false).await;
+                }
+                Err(err) => return Err(MCManageError::InvalidFile(path, err.to_string())),
+            };
+
+            match raw.get(name) {
+                Some(file) => MCServerType::from_file(name, file.clone()),
+                None => Err(MCManageError::UnknownServerType(name.to_string())),
+            }
+        })
+    }
+
+    /// Lists every server type name configured in `mcserver_types.json`
+    /// under `config_dir`, for a UI building a "create server" form.
+    ///
+    /// Like [`Self::get`], a missing/invalid file is regenerated and the
+    /// read retried exactly once before giving up, unless
+    /// `confirm_regeneration` is `false`.
+    pub async fn available_types(config_dir: &Path, confirm_regeneration: bool) -> Result<Vec<String>, MCManageError> {
+        Self::available_types_with_retry(config_dir, confirm_regeneration).await
+    }
+
+    fn available_types_with_retry<'a>(
+        config_dir: &'a Path,
+        allow_regenerate: bool,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<String>, MCManageError>> + 'a>> {
+        Box::pin(async move {
+            let path = config_dir.join("mcserver_types.json");
+
+            let content = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound && allow_regenerate => {
+                    Self::generate_default_file(config_dir).await?;
+                    return Self::available_types_with_retry(config_dir, false).await;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let raw: HashMap<String, MCServerTypeFile> = match serde_json::from_str(&content) {
+                Ok(raw) => raw,
+                Err(_) if allow_regenerate => {
+                    Self::generate_default_file(config_dir).await?;
+                    return Self::available_types_with_retry(config_dir, false).await;
+                }
+                Err(err) => return Err(MCManageError::InvalidFile(path, err.to_string())),
+            };
+
+            Ok(raw.into_keys().collect())
+        })
+    }
+
+    /// Writes the embedded default `mcserver_types.json` to `config_dir`,
+    /// creating the directory if necessary.
+    async fn generate_default_file(config_dir: &Path) -> Result<(), MCManageError> {
+        tokio::fs::create_dir_all(config_dir).await?;
+        let mut defaults = HashMap::new();
+        defaults.insert(
+            "vanilla".to_string(),
+            MCServerTypeFile {
+                started: vec!["Done".into()],
+                started_regex: vec![],
+                player_name_pos: None,
+                player_name_anchor: None,
+                startup_grace_secs: None,
+            crash_report_pattern: None,
+        },
+        );
+        let content = serde_json::to_string_pretty(&defaults)
+            .expect("MCServerTypeFile serialization cannot fail");
+        tokio::fs::write(config_dir.join("mcserver_types.json"), content).await?;
+        Ok(())
+    }
+}
+
+/// A cache of every [`MCServerType`] loaded from `mcserver_types.json`,
+/// shared (lock-free, via [`ArcSwap`]) between every task that needs to look
+/// up a server type without re-reading and re-parsing the file on every
+/// lookup.
+///
+/// The cache is only ever refreshed explicitly via [`Self::reload`], e.g.
+/// when an operator edits `mcserver_types.json` and wants the running
+/// application to pick up the change without a restart.
+pub struct MCServerTypeCache {
+    config_dir: PathBuf,
+    types: ArcSwap<HashMap<String, MCServerType>>,
+}
+
+impl MCServerTypeCache {
+    /// Loads every entry from `mcserver_types.json` under `config_dir` and
+    /// builds a cache around it.
+    pub async fn load(config_dir: impl Into<PathBuf>) -> Result<Self, MCManageError> {
+        let config_dir = config_dir.into();
+        let types = MCServerType::load_all(&config_dir).await?;
+        Ok(Self {
+            config_dir,
+            types: ArcSwap::from_pointee(types),
+        })
+    }
+
+    /// Looks up a cached server type by name, without touching disk.
+    pub fn get(&self, name: &str) -> Option<MCServerType> {
+        self.types.load().get(name).cloned()
+    }
+
+    /// Re-reads and re-parses `mcserver_types.json`, swapping the cached
+    /// data only if the file still parses successfully. A malformed edit is
+    /// rejected and the previously cached data is left in place, so a typo
+    /// in the file never leaves the cache empty or half-updated.
+    pub async fn reload(&self) -> Result<(), MCManageError> {
+        let types = MCServerType::load_all(&self.config_dir).await?;
+        self.types.store(Arc::new(types));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_configured_phrase_triggers_detection() {
+        let server_type = MCServerType::from_file(
+            "vanilla",
+            MCServerTypeFile {
+                started: vec!["Done".into(), "For help, type \"help\"".into()],
+                started_regex: vec![],
+                player_name_pos: None,
+                player_name_anchor: None,
+            startup_grace_secs: None,
+        crash_report_pattern: None,
+    },
+        )
+        .unwrap();
+
+        assert!(server_type.matches_started("[Server] For help, type \"help\""));
+        assert!(!server_type.matches_started("[Server] Stopping the server"));
+    }
+
+    #[test]
+    fn regex_pattern_triggers_detection() {
+        let server_type = MCServerType::from_file(
+            "modded",
+            MCServerTypeFile {
+                started: vec![],
+                started_regex: vec![r"Done \(\d+\.\d+s\)!".into()],
+                player_name_pos: None,
+                player_name_anchor: None,
+            startup_grace_secs: None,
+        crash_report_pattern: None,
+    },
+        )
+        .unwrap();
+
+        assert!(server_type.matches_started("Done (12.3s)! For help, type \"help\""));
+        assert!(!server_type.matches_started("Starting up"));
+    }
+
+    #[test]
+    fn get_player_name_joined_extracts_the_configured_word() {
+        let server_type = MCServerType::from_file(
+            "vanilla",
+            MCServerTypeFile {
+                started: vec![],
+                started_regex: vec![],
+                player_name_pos: Some(3),
+                player_name_anchor: None,
+            startup_grace_secs: None,
+        crash_report_pattern: None,
+    },
+        )
+        .unwrap();
+
+        assert_eq!(
+            server_type.get_player_name_joined("[12:34:56] [Server thread/INFO]: Steve joined the game"),
+            Some("Steve".to_string())
+        );
+    }
+
+    #[test]
+    fn get_player_name_joined_skips_a_line_shorter_than_the_configured_position() {
+        let server_type = MCServerType::from_file(
+            "vanilla",
+            MCServerTypeFile {
+                started: vec![],
+                started_regex: vec![],
+                player_name_pos: Some(10),
+                player_name_anchor: None,
+            startup_grace_secs: None,
+        crash_report_pattern: None,
+    },
+        )
+        .unwrap();
+
+        assert_eq!(server_type.get_player_name_joined("too short"), None);
+    }
+
+    #[test]
+    fn get_player_name_joined_returns_none_without_a_configured_position() {
+        let server_type = MCServerType::from_file(
+            "vanilla",
+            MCServerTypeFile {
+                started: vec![],
+                started_regex: vec![],
+                player_name_pos: None,
+                player_name_anchor: None,
+            startup_grace_secs: None,
+        crash_report_pattern: None,
+    },
+        )
+        .unwrap();
+
+        assert_eq!(
+            server_type.get_player_name_joined("[12:34:56] [Server thread/INFO]: Steve joined the game"),
+            None
+        );
+    }
+
+    #[test]
+    fn get_player_name_joined_extracts_the_name_when_the_configured_anchor_is_present() {
+        let server_type = MCServerType::from_file(
+            "vanilla",
+            MCServerTypeFile {
+                started: vec![],
+                started_regex: vec![],
+                player_name_pos: Some(3),
+                player_name_anchor: Some("INFO]:".to_string()),
+                startup_grace_secs: None,
+            crash_report_pattern: None,
+        },
+        )
+        .unwrap();
+
+        assert_eq!(
+            server_type.get_player_name_joined("[12:34:56] [Server thread/INFO]: Steve joined the game"),
+            Some("Steve".to_string())
+        );
+    }
+
+    #[test]
+    fn get_player_name_joined_rejects_a_chat_message_spoofing_a_join_line_without_the_anchor() {
+        let server_type = MCServerType::from_file(
+            "vanilla",
+            MCServerTypeFile {
+                started: vec![],
+                started_regex: vec![],
+                player_name_pos: Some(3),
+                player_name_anchor: Some("INFO]:".to_string()),
+                startup_grace_secs: None,
+            crash_report_pattern: None,
+        },
+        )
+        .unwrap();
+
+        // Same word position and wording as a real join line, but sent as
+        // a chat message rather than emitted by the server itself, so it
+        // lacks the "INFO]:" log-level/tag prefix the anchor requires.
+        assert_eq!(
+            server_type.get_player_name_joined("[12:34:56] [Server thread/CHAT]: <Griefer> Steve joined the game"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn load_all_reads_multiple_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}}"#,
+        )
+        .unwrap();
+
+        let types = MCServerType::load_all(dir.path()).await.unwrap();
+        assert!(types["vanilla"].matches_started("Done!"));
+    }
+
+    #[tokio::test]
+    async fn available_types_lists_the_known_type_names() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}, "modded": {"started": ["Done"]}}"#,
+        )
+        .unwrap();
+
+        let mut types = MCServerType::available_types(dir.path(), true).await.unwrap();
+        types.sort();
+        assert_eq!(types, vec!["modded".to_string(), "vanilla".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn available_types_regenerates_a_missing_file_and_returns_the_embedded_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+
+        let types = MCServerType::available_types(&config_dir, true).await.unwrap();
+        assert_eq!(types, vec!["vanilla".to_string()]);
+        assert!(config_dir.join("mcserver_types.json").is_file());
+    }
+
+    #[tokio::test]
+    async fn get_missing_type_name_returns_unknown_server_type() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}}"#,
+        )
+        .unwrap();
+
+        let result = MCServerType::get("modded", dir.path(), true).await;
+        assert!(matches!(result, Err(MCManageError::UnknownServerType(name)) if name == "modded"));
+    }
+
+    #[tokio::test]
+    async fn get_regenerates_invalid_file_once_then_succeeds() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("mcserver_types.json"), "not json").unwrap();
+
+        let result = MCServerType::get("vanilla", dir.path(), true).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn get_with_confirm_regeneration_false_errors_without_touching_an_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcserver_types.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let result = MCServerType::get("vanilla", dir.path(), false).await;
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not json");
+    }
+
+    #[tokio::test]
+    async fn available_types_with_confirm_regeneration_false_errors_without_touching_an_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcserver_types.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let result = MCServerType::available_types(dir.path(), false).await;
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not json");
+    }
+
+    #[tokio::test]
+    async fn get_with_confirm_regeneration_false_errors_without_creating_a_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+
+        let result = MCServerType::get("vanilla", &config_dir, false).await;
+        assert!(result.is_err());
+        assert!(!config_dir.join("mcserver_types.json").exists());
+    }
+
+    #[tokio::test]
+    async fn get_missing_directory_regenerates_and_succeeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+
+        let result = MCServerType::get("vanilla", &config_dir, true).await;
+        assert!(result.is_ok());
+        assert!(config_dir.join("mcserver_types.json").is_file());
+    }
+
+    #[tokio::test]
+    async fn generate_default_file_creates_the_config_dir_and_tolerates_it_already_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+        assert!(!config_dir.exists());
+
+        MCServerType::generate_default_file(&config_dir).await.unwrap();
+        assert!(config_dir.is_dir());
+        assert!(config_dir.join("mcserver_types.json").is_file());
+
+        // Regenerating against an already-existing config dir must not
+        // panic or error.
+        MCServerType::generate_default_file(&config_dir).await.unwrap();
+        assert!(config_dir.join("mcserver_types.json").is_file());
+    }
+
+    #[tokio::test]
+    async fn get_returns_an_error_instead_of_looping_when_regeneration_cant_help() {
+        let dir = tempfile::tempdir().unwrap();
+        // A file where the directory should be: reading the types file
+        // under it fails with a not-a-directory error rather than
+        // NotFound, so no regeneration is attempted and that IO error is
+        // returned as is.
+        let config_dir = dir.path().join("config");
+        std::fs::write(&config_dir, "not a directory").unwrap();
+
+        let result = MCServerType::get("vanilla", &config_dir, true).await;
+        assert!(matches!(result, Err(MCManageError::IOError(_))));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_does_not_block_other_tasks_on_a_single_threaded_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}}"#,
+        )
+        .unwrap();
+
+        let progressed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&progressed);
+        let background = tokio::spawn(async move {
+            loop {
+                counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                tokio::task::yield_now().await;
+            }
+        });
+
+        MCServerType::get("vanilla", dir.path(), true).await.unwrap();
+
+        background.abort();
+        assert!(
+            progressed.load(std::sync::atomic::Ordering::Relaxed) > 0,
+            "the background task should have made progress while get() awaited its async fs read"
+        );
+    }
+
+    #[tokio::test]
+    async fn reload_picks_up_a_valid_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}}"#,
+        )
+        .unwrap();
+
+        let cache = MCServerTypeCache::load(dir.path()).await.unwrap();
+        assert!(cache.get("modded").is_none());
+
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}, "modded": {"started": ["Loaded"]}}"#,
+        )
+        .unwrap();
+
+        cache.reload().await.unwrap();
+        assert!(cache.get("modded").unwrap().matches_started("Loaded"));
+    }
+
+    #[tokio::test]
+    async fn reload_rejects_a_malformed_edit_and_keeps_the_previous_data() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("mcserver_types.json"),
+            r#"{"vanilla": {"started": ["Done"]}}"#,
+        )
+        .unwrap();
+
+        let cache = MCServerTypeCache::load(dir.path()).await.unwrap();
+
+        std::fs::write(dir.path().join("mcserver_types.json"), "not json").unwrap();
+
+        let result = cache.reload().await;
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+        assert!(cache.get("vanilla").unwrap().matches_started("Done"));
+    }
+}
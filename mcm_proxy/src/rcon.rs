@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use mcm_misc::MCManageError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// RCON packet types, per the [Source RCON protocol](https://developer.valvesoftware.com/wiki/Source_RCON_Protocol)
+/// Minecraft's built-in RCON server implements.
+const PACKET_TYPE_COMMAND: i32 = 2;
+const PACKET_TYPE_LOGIN: i32 = 3;
+
+/// A live RCON connection, authenticated and ready to run commands; see
+/// [`RconConnection::connect`] and [`crate::MCServer::rcon_command`].
+///
+/// One connection is reused across calls rather than reconnecting every
+/// time, but any error (including a timed-out command) drops it, so the
+/// next call starts from a fresh connection instead of reusing one left in
+/// an unknown state.
+pub struct RconConnection {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconConnection {
+    /// Connects to `addr` and authenticates with `password`. Fails with
+    /// [`MCManageError::NotReady`] if the server rejects the password.
+    pub async fn connect(addr: &str, password: &str) -> Result<Self, MCManageError> {
+        let stream = TcpStream::connect(addr).await?;
+        let mut connection = Self { stream, next_id: 1 };
+
+        let sent_id = connection.send_packet(PACKET_TYPE_LOGIN, password).await?;
+        let (reply_id, _) = connection.read_packet().await?;
+        if reply_id != sent_id {
+            return Err(MCManageError::NotReady("RCON authentication was rejected".to_string()));
+        }
+
+        Ok(connection)
+    }
+
+    /// Sends `command` and returns the server's response body, aborting
+    /// (and consuming `self`, so a stale connection can't be reused) if no
+    /// reply arrives within `command_timeout`.
+    pub async fn command(mut self, command: &str, command_timeout: Duration) -> Result<(Self, String), MCManageError> {
+        let body = timeout(command_timeout, self.command_inner(command))
+            .await
+            .map_err(|_| MCManageError::NotReady(format!("RCON command timed out after {command_timeout:?}")))??;
+        Ok((self, body))
+    }
+
+    async fn command_inner(&mut self, command: &str) -> Result<String, MCManageError> {
+        let sent_id = self.send_packet(PACKET_TYPE_COMMAND, command).await?;
+        let (reply_id, body) = self.read_packet().await?;
+        if reply_id != sent_id {
+            return Err(MCManageError::InvalidMessage("RCON response id did not match the request".to_string()));
+        }
+        Ok(body)
+    }
+
+    async fn send_packet(&mut self, packet_type: i32, body: &str) -> Result<i32, MCManageError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut payload = Vec::with_capacity(body.len() + 10);
+        payload.extend_from_slice(&id.to_le_bytes());
+        payload.extend_from_slice(&packet_type.to_le_bytes());
+        payload.extend_from_slice(body.as_bytes());
+        payload.extend_from_slice(&[0, 0]);
+
+        self.stream.write_all(&(payload.len() as i32).to_le_bytes()).await?;
+        self.stream.write_all(&payload).await?;
+        Ok(id)
+    }
+
+    async fn read_packet(&mut self) -> Result<(i32, String), MCManageError> {
+        let mut len_buf = [0u8; 4];
+        self.stream.read_exact(&mut len_buf).await?;
+        let len = i32::from_le_bytes(len_buf).max(0) as usize;
+
+        let mut buf = vec![0u8; len];
+        self.stream.read_exact(&mut buf).await?;
+        if buf.len() < 10 {
+            return Err(MCManageError::InvalidMessage("RCON packet shorter than its fixed header".to_string()));
+        }
+
+        let id = i32::from_le_bytes(buf[0..4].try_into().expect("slice is exactly 4 bytes"));
+        // Trailing two NUL bytes pad every packet; the body sits between
+        // the 8-byte id/type header and that padding.
+        let body = String::from_utf8_lossy(&buf[8..buf.len() - 2]).into_owned();
+        Ok((id, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn encode_packet(id: i32, packet_type: i32, body: &str) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&id.to_le_bytes());
+        payload.extend_from_slice(&packet_type.to_le_bytes());
+        payload.extend_from_slice(body.as_bytes());
+        payload.extend_from_slice(&[0, 0]);
+
+        let mut packet = (payload.len() as i32).to_le_bytes().to_vec();
+        packet.extend_from_slice(&payload);
+        packet
+    }
+
+    async fn read_packet(stream: &mut TcpStream) -> (i32, i32, String) {
+        let mut len_buf = [0u8; 4];
+        stream.read_exact(&mut len_buf).await.unwrap();
+        let len = i32::from_le_bytes(len_buf) as usize;
+        let mut buf = vec![0u8; len];
+        stream.read_exact(&mut buf).await.unwrap();
+        let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+        let packet_type = i32::from_le_bytes(buf[4..8].try_into().unwrap());
+        let body = String::from_utf8_lossy(&buf[8..buf.len() - 2]).into_owned();
+        (id, packet_type, body)
+    }
+
+    #[tokio::test]
+    async fn connect_authenticates_and_command_round_trips_a_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let (login_id, packet_type, password) = read_packet(&mut stream).await;
+            assert_eq!(packet_type, PACKET_TYPE_LOGIN);
+            assert_eq!(password, "secret");
+            stream.write_all(&encode_packet(login_id, 2, "")).await.unwrap();
+
+            let (command_id, packet_type, command) = read_packet(&mut stream).await;
+            assert_eq!(packet_type, PACKET_TYPE_COMMAND);
+            assert_eq!(command, "list");
+            stream
+                .write_all(&encode_packet(command_id, 0, "There are 2 players online"))
+                .await
+                .unwrap();
+        });
+
+        let connection = RconConnection::connect(&addr, "secret").await.unwrap();
+        let (_connection, response) = connection.command("list", Duration::from_secs(5)).await.unwrap();
+        assert_eq!(response, "There are 2 players online");
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connect_fails_when_the_server_rejects_the_password() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (_login_id, _, _) = read_packet(&mut stream).await;
+            // A rejected login replies with id -1, per the protocol.
+            stream.write_all(&encode_packet(-1, 2, "")).await.unwrap();
+        });
+
+        let result = RconConnection::connect(&addr, "wrong").await;
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn command_times_out_instead_of_blocking_forever_on_a_stalled_reply() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (login_id, _, _) = read_packet(&mut stream).await;
+            stream.write_all(&encode_packet(login_id, 2, "")).await.unwrap();
+
+            // Accepts the command but never replies, simulating a hung
+            // server instead of closing the connection outright.
+            let _ = read_packet(&mut stream).await;
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let connection = RconConnection::connect(&addr, "secret").await.unwrap();
+        let result = connection.command("list", Duration::from_millis(50)).await;
+
+        assert!(matches!(result, Err(MCManageError::NotReady(_))));
+        server.abort();
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use mcm_misc::{Config, MCManageError};
+use serde::{Deserialize, Serialize};
+
+use crate::mcserver::MCServer;
+
+/// A named set of defaults in `server_list.json`'s `templates` map that a
+/// [`ServerListEntry`] can inherit by name via [`ServerListEntry::template`],
+/// so operators managing many similar servers don't have to repeat the same
+/// `jvm_args`/`args`/`server_type` on every entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerTemplate {
+    #[serde(default)]
+    pub server_type: String,
+    /// Flags passed to the `java` binary itself (e.g. `-Xmx2G -Xms2G`),
+    /// before `-jar`.
+    #[serde(default)]
+    pub jvm_args: Vec<String>,
+    /// Arguments passed to the server jar (e.g. `nogui`), after `-jar`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// A single entry in `server_list.json`: a managed server's name and which
+/// configured [`crate::MCServerType`] it uses.
+///
+/// An entry may reference a [`ServerTemplate`] by name via
+/// [`Self::template`] to inherit its `server_type`/`jvm_args`/`args`; any of
+/// those fields the entry sets itself (non-empty, for the `Vec` fields)
+/// overrides the template's. See [`load_mcserver_list`] for how a template
+/// and its entries are resolved into concrete [`MCServer`]s.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerListEntry {
+    pub name: String,
+    #[serde(default)]
+    pub server_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jvm_args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Whether [`crate::MCServerManager::start_all`]'s auto-start loop may
+    /// start this server; see [`MCServer::with_enabled`](crate::MCServer::with_enabled).
+    /// Defaults to `true`. A disabled entry is still constructed and
+    /// managed normally — it can always be started explicitly.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Overrides `Config::agree_to_eula` for this server only; see
+    /// [`MCServer::with_agree_to_eula`](crate::MCServer::with_agree_to_eula).
+    /// `None` (the default) defers to the global config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agree_to_eula: Option<bool>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for ServerListEntry {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            server_type: String::new(),
+            template: None,
+            jvm_args: Vec::new(),
+            args: Vec::new(),
+            enabled: true,
+            agree_to_eula: None,
+        }
+    }
+}
+
+/// The on-disk shape of `server_list.json` and any files it `include`s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ServerListFile {
+    #[serde(default)]
+    servers: Vec<ServerListEntry>,
+    /// Paths to further `server_list.json`-shaped files, resolved relative
+    /// to this file's directory and merged into the result, so large
+    /// deployments can split their server list into per-group files.
+    #[serde(default)]
+    include: Vec<String>,
+    /// Named templates entries may inherit from via [`ServerListEntry::template`].
+    #[serde(default)]
+    templates: HashMap<String, ServerTemplate>,
+}
+
+/// Loads `path`, merging any files listed in its `include` key, and returns
+/// every entry keyed by name.
+///
+/// Returns [`MCManageError::InvalidFile`] if the same server name appears
+/// in more than one of the merged files.
+pub fn load_server_list(path: &Path) -> Result<HashMap<String, ServerListEntry>, MCManageError> {
+    let mut entries = HashMap::new();
+    let mut templates = HashMap::new();
+    load_into(path, &mut entries, &mut templates)?;
+    Ok(entries)
+}
+
+/// Like [`load_server_list`], but resolves each entry's [`ServerTemplate`]
+/// (if it names one) and returns a concrete, launch-ready [`MCServer`] per
+/// entry instead of the raw JSON shape.
+///
+/// An entry's `jvm_args`/`args` override the template's wholesale when
+/// non-empty; otherwise the template's are used. The resolved `jvm_args`
+/// and `args` are combined into the command line passed to
+/// [`MCServer::with_command`]: `java`, then `jvm_args`, then `-jar
+/// server.jar`, then `args`.
+pub fn load_mcserver_list(
+    path: &Path,
+    config: Arc<Config>,
+) -> Result<HashMap<String, Arc<MCServer>>, MCManageError> {
+    let mut entries = HashMap::new();
+    let mut templates = HashMap::new();
+    load_into(path, &mut entries, &mut templates)?;
+
+    let mut servers = HashMap::new();
+    for (name, entry) in entries {
+        let template = entry
+            .template
+            .as_ref()
+            .map(|template_name| {
+                templates.get(template_name).ok_or_else(|| {
+                    MCManageError::InvalidFile(
+                        path.to_path_buf(),
+                        format!("server '{name}' references unknown template '{template_name}'"),
+                    )
+                })
+            })
+            .transpose()?;
+
+        let server = build_mcserver(&name, &entry, template, Arc::clone(&config));
+        servers.insert(name, Arc::new(server));
+    }
+
+    Ok(servers)
+}
+
+/// Resolves `entry` (falling back to `template`'s `server_type`/`jvm_args`/
+/// `args` wherever `entry` doesn't set its own non-empty value) into a
+/// launch-ready [`MCServer`] named `name`; see [`load_mcserver_list`] and
+/// [`crate::mcserver_manager::MCServerManager::update_server`].
+pub(crate) fn build_mcserver(
+    name: &str,
+    entry: &ServerListEntry,
+    template: Option<&ServerTemplate>,
+    config: Arc<Config>,
+) -> MCServer {
+    let server_type = if !entry.server_type.is_empty() {
+        entry.server_type.clone()
+    } else {
+        template.map(|t| t.server_type.clone()).unwrap_or_default()
+    };
+    let jvm_args = if !entry.jvm_args.is_empty() {
+        entry.jvm_args.clone()
+    } else {
+        template.map(|t| t.jvm_args.clone()).unwrap_or_default()
+    };
+    let args = if !entry.args.is_empty() {
+        entry.args.clone()
+    } else {
+        template.map(|t| t.args.clone()).unwrap_or_default()
+    };
+
+    let mut command_args = jvm_args;
+    command_args.push("-jar".to_string());
+    command_args.push("server.jar".to_string());
+    command_args.extend(args);
+
+    let mut server = MCServer::new(name, config)
+        .with_command("java", command_args)
+        .with_enabled(entry.enabled);
+    if !server_type.is_empty() {
+        server = server.with_server_type(server_type);
+    }
+    if let Some(agree_to_eula) = entry.agree_to_eula {
+        server = server.with_agree_to_eula(agree_to_eula);
+    }
+    server
+}
+
+/// Parses `content` (read from `path`, used only for error messages) as a
+/// [`ServerListFile`], transparently upgrading the legacy shape — a bare
+/// JSON array of [`ServerListEntry`], with no `include`/`templates` support
+/// — to the current one. See [`migrate_server_list`] to persist the
+/// upgrade back to disk.
+fn parse_server_list_file(content: &str, path: &Path) -> Result<ServerListFile, MCManageError> {
+    let invalid = |err: serde_json::Error| MCManageError::InvalidFile(path.to_path_buf(), err.to_string());
+
+    if content.trim_start().starts_with('[') {
+        let servers: Vec<ServerListEntry> = serde_json::from_str(content).map_err(invalid)?;
+        return Ok(ServerListFile {
+            servers,
+            include: Vec::new(),
+            templates: HashMap::new(),
+        });
+    }
+
+    serde_json::from_str(content).map_err(invalid)
+}
+
+/// Upgrades `path` in place if it's still in the legacy bare-array shape:
+/// the original is first copied to `{path}.bak`, then `path` itself is
+/// overwritten with the same entries in the current object shape (no
+/// `include`s or `templates`, since the legacy shape couldn't express
+/// either). Does nothing (and returns `Ok(false)`) if `path` is already in
+/// the current shape.
+///
+/// Only `path` itself is considered — any files it `include`s are left
+/// untouched, since [`load_server_list`] resolves those independently and
+/// each would need migrating on its own terms.
+///
+/// If `confirm_regeneration` is `false`, a file that WOULD be migrated is
+/// instead left completely untouched and [`MCManageError::InvalidFile`] is
+/// returned, so an operator who wants to review a legacy file before it's
+/// backed up and overwritten can opt out of the automatic upgrade. Passing
+/// `true` preserves the previous always-migrate behavior.
+pub fn migrate_server_list(path: &Path, confirm_regeneration: bool) -> Result<bool, MCManageError> {
+    let content = fs::read_to_string(path)?;
+    if !content.trim_start().starts_with('[') {
+        return Ok(false);
+    }
+
+    let file = parse_server_list_file(&content, path)?;
+    if !confirm_regeneration {
+        return Err(MCManageError::InvalidFile(
+            path.to_path_buf(),
+            "still in the legacy bare-array shape; refusing to migrate it because confirm_regeneration is disabled"
+                .to_string(),
+        ));
+    }
+    fs::copy(path, path.with_extension("json.bak"))?;
+
+    let migrated = serde_json::to_string_pretty(&file)
+        .map_err(|err| MCManageError::InvalidFile(path.to_path_buf(), err.to_string()))?;
+    write_atomic(path, &migrated)?;
+
+    Ok(true)
+}
+
+/// Writes `content` to `path` without risking a truncated file if the
+/// process is interrupted mid-write: `content` is written to a sibling
+/// temp file first, then moved into place with a single atomic rename, so
+/// `path` always either holds its previous complete contents or its new
+/// ones, never a partial write. Used by every operation that persists
+/// `server_list.json` or another config/state file in place (see
+/// [`crate::mcserver_manager::MCServerManager::save_state`] and
+/// [`crate::mcserver_manager::MCServerManager::detach`]).
+pub(crate) fn write_atomic(path: &Path, content: &str) -> Result<(), MCManageError> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn load_into(
+    path: &Path,
+    entries: &mut HashMap<String, ServerListEntry>,
+    templates: &mut HashMap<String, ServerTemplate>,
+) -> Result<(), MCManageError> {
+    let content = fs::read_to_string(path)?;
+    let file = parse_server_list_file(&content, path)?;
+
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    for include in &file.include {
+        load_into(&dir.join(include), entries, templates)?;
+    }
+
+    for (template_name, template) in file.templates {
+        if templates.insert(template_name.clone(), template).is_some() {
+            return Err(MCManageError::InvalidFile(
+                path.to_path_buf(),
+                format!("duplicate template name across includes: '{template_name}'"),
+            ));
+        }
+    }
+
+    for entry in file.servers {
+        let name = entry.name.clone();
+        if entries.insert(name.clone(), entry).is_some() {
+            return Err(MCManageError::InvalidFile(
+                path.to_path_buf(),
+                format!("duplicate server name '{name}': two entries would collide on the same logs file and server path"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn merges_servers_from_included_files() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("groups")).unwrap();
+        fs::write(
+            dir.path().join("groups/survival.json"),
+            r#"{"servers": [{"name": "survival", "server_type": "vanilla"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("groups/creative.json"),
+            r#"{"servers": [{"name": "creative", "server_type": "vanilla"}]}"#,
+        )
+        .unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"{"servers": [{"name": "lobby", "server_type": "vanilla"}], "include": ["groups/survival.json", "groups/creative.json"]}"#,
+        )
+        .unwrap();
+
+        let entries = load_server_list(&main).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.contains_key("lobby"));
+        assert!(entries.contains_key("survival"));
+        assert!(entries.contains_key("creative"));
+    }
+
+    #[test]
+    fn load_server_list_parses_a_legacy_bare_array() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"[{"name": "lobby", "server_type": "vanilla"}, {"name": "survival", "server_type": "modded"}]"#,
+        )
+        .unwrap();
+
+        let entries = load_server_list(&main).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries["lobby"].server_type, "vanilla");
+        assert_eq!(entries["survival"].server_type, "modded");
+    }
+
+    #[test]
+    fn migrate_server_list_upgrades_a_legacy_file_and_backs_up_the_original() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        let original = r#"[{"name": "lobby", "server_type": "vanilla"}]"#;
+        fs::write(&main, original).unwrap();
+
+        let migrated = migrate_server_list(&main, true).unwrap();
+        assert!(migrated);
+
+        let backup = fs::read_to_string(main.with_extension("json.bak")).unwrap();
+        assert_eq!(backup, original);
+
+        let entries = load_server_list(&main).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries["lobby"].server_type, "vanilla");
+    }
+
+    #[test]
+    fn write_atomic_leaves_the_original_intact_if_interrupted_before_the_rename() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("server_list.json");
+        fs::write(&path, "original").unwrap();
+
+        // Simulate a crash that lands after the temp file is written but
+        // before the rename that publishes it: a stray, possibly
+        // truncated temp file next to `path` must not affect `path`.
+        fs::write(path.with_extension("tmp"), "truncat").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+
+        write_atomic(&path, "updated").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "updated");
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn migrate_server_list_is_a_no_op_for_an_already_current_file() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(&main, r#"{"servers": [{"name": "lobby", "server_type": "vanilla"}]}"#).unwrap();
+
+        let migrated = migrate_server_list(&main, true).unwrap();
+        assert!(!migrated);
+        assert!(!main.with_extension("json.bak").exists());
+    }
+
+    #[test]
+    fn migrate_server_list_with_confirm_regeneration_false_errors_without_touching_the_file() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        let original = r#"[{"name": "lobby", "server_type": "vanilla"}]"#;
+        fs::write(&main, original).unwrap();
+
+        let result = migrate_server_list(&main, false);
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+
+        assert_eq!(fs::read_to_string(&main).unwrap(), original);
+        assert!(!main.with_extension("json.bak").exists());
+    }
+
+    #[test]
+    fn errors_on_a_duplicate_name_across_includes() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("groups")).unwrap();
+        fs::write(
+            dir.path().join("groups/survival.json"),
+            r#"{"servers": [{"name": "lobby", "server_type": "vanilla"}]}"#,
+        )
+        .unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"{"servers": [{"name": "lobby", "server_type": "vanilla"}], "include": ["groups/survival.json"]}"#,
+        )
+        .unwrap();
+
+        let result = load_server_list(&main);
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+    }
+
+    #[test]
+    fn errors_on_a_duplicate_name_within_the_same_file() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"{"servers": [
+                {"name": "lobby", "server_type": "vanilla"},
+                {"name": "lobby", "server_type": "modded"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let result = load_server_list(&main);
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+    }
+
+    #[test]
+    fn load_mcserver_list_resolves_template_args_and_lets_an_entry_override_them() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"{
+                "templates": {
+                    "survival": {"server_type": "vanilla", "jvm_args": ["-Xmx2G", "-Xms2G"], "args": ["nogui"]}
+                },
+                "servers": [
+                    {"name": "alpha", "template": "survival"},
+                    {"name": "beta", "template": "survival", "jvm_args": ["-Xmx4G", "-Xms4G"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let servers = load_mcserver_list(&main, config).unwrap();
+
+        let alpha = &servers["alpha"];
+        assert_eq!(alpha.server_type(), Some("vanilla"));
+        assert_eq!(
+            alpha.args(),
+            ["-Xmx2G", "-Xms2G", "-jar", "server.jar", "nogui"]
+        );
+
+        let beta = &servers["beta"];
+        assert_eq!(beta.server_type(), Some("vanilla"));
+        assert_eq!(
+            beta.args(),
+            ["-Xmx4G", "-Xms4G", "-jar", "server.jar", "nogui"]
+        );
+    }
+
+    #[test]
+    fn load_mcserver_list_lets_an_entry_override_agree_to_eula() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"{
+                "servers": [
+                    {"name": "alpha", "agree_to_eula": true},
+                    {"name": "beta"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let servers = load_mcserver_list(&main, config).unwrap();
+
+        assert_eq!(servers["alpha"].agree_to_eula_override(), Some(true));
+        assert_eq!(servers["beta"].agree_to_eula_override(), None);
+    }
+
+    #[test]
+    fn load_mcserver_list_errors_on_an_unknown_template() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("server_list.json");
+        fs::write(
+            &main,
+            r#"{"servers": [{"name": "alpha", "template": "does-not-exist"}]}"#,
+        )
+        .unwrap();
+
+        let config = Arc::new(Config::new(dir.path().to_path_buf(), false));
+        let result = load_mcserver_list(&main, config);
+        assert!(matches!(result, Err(MCManageError::InvalidFile(_, _))));
+    }
+}